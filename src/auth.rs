@@ -0,0 +1,167 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use crate::error::{Error, Result};
+use crate::http::{HttpClient, HttpRequest, HttpResponse};
+use crate::secrets::SecretStore;
+
+// A header's name and value.
+pub type Header = (String, String);
+
+// A header whose value is a secret, as podcasts keep them.
+pub type SecretHeader = (String, Secret);
+
+// A password, token, or header value. It's either kept as-is in the storage, or refers to an entry in the encrypted
+// secrets file by name.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Secret
+{
+    Plain(String),
+    Stored(String)
+}
+
+impl Secret
+{
+    pub fn resolve(&self, secrets: &SecretStore) -> Result<String>
+    {
+        match self
+        {
+            Secret::Plain(value) => Ok(value.clone()),
+            Secret::Stored(name) => secrets.get(name).map(str::to_string).ok_or_else(||
+                Error::NotFound(format!("The secret '{}' doesn't exist. Add it with the 'secret set' command!", name)))
+        }
+    }
+
+    // Stored secrets are described by name, which is fine to show since the value stays encrypted.
+    fn describe(&self, show_secrets: bool) -> String
+    {
+        match self
+        {
+            Secret::Plain(value) if show_secrets => format!("'{}'", value),
+            Secret::Plain(_) => String::from("(hidden)"),
+            Secret::Stored(name) => format!("from secret '{}'", name)
+        }
+    }
+}
+
+// How a private feed proves who's asking. Used for the feed itself and for its episodes.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub enum FeedAuth
+{
+    #[default]
+    None,
+    Basic { username: String, password: Secret },
+    Bearer { token: Secret }
+}
+
+// Everything a podcast sends along with its requests.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FeedCredentials
+{
+    pub auth: FeedAuth,
+    pub headers: Vec<SecretHeader>
+}
+
+impl FeedCredentials
+{
+    pub fn is_empty(&self) -> bool
+    {
+        self.auth == FeedAuth::None && self.headers.is_empty()
+    }
+
+    // Whether any of these credentials live in the secrets file, which then has to be unlocked.
+    pub fn uses_stored_secrets(&self) -> bool
+    {
+        let stored_auth = match &self.auth
+        {
+            FeedAuth::None => false,
+            FeedAuth::Basic { password: secret, .. } | FeedAuth::Bearer { token: secret } => matches!(secret, Secret::Stored(_))
+        };
+
+        stored_auth || self.headers.iter().any(|(_, value)| matches!(value, Secret::Stored(_)))
+    }
+
+    pub fn request_headers(&self, secrets: &SecretStore) -> Result<Vec<Header>>
+    {
+        let mut headers = self.headers.iter()
+            .map(|(name, value)| Ok((name.clone(), value.resolve(secrets)?)))
+            .collect::<Result<Vec<Header>>>()?;
+
+        match &self.auth
+        {
+            FeedAuth::None => {}
+            FeedAuth::Basic { username, password } =>
+            {
+                let encoded = STANDARD.encode(format!("{}:{}", username, password.resolve(secrets)?));
+                headers.push((String::from("Authorization"), format!("Basic {}", encoded)));
+            }
+            FeedAuth::Bearer { token } => headers.push((String::from("Authorization"), format!("Bearer {}", token.resolve(secrets)?)))
+        }
+
+        Ok(headers)
+    }
+
+    // A description that's safe to print. Plain passwords, tokens and header values are hidden unless 'show_secrets' is
+    // set, since headers often carry keys too.
+    pub fn describe(&self, show_secrets: bool) -> Option<String>
+    {
+        if self.is_empty() { return None }
+
+        let mut parts = Vec::new();
+        match &self.auth
+        {
+            FeedAuth::None => {}
+            FeedAuth::Basic { username, password } => parts.push(format!("Basic auth as '{}', password {}", username, password.describe(show_secrets))),
+            FeedAuth::Bearer { token } => parts.push(format!("Bearer token {}", token.describe(show_secrets)))
+        }
+
+        for (name, value) in &self.headers { parts.push(format!("header '{}' {}", name, value.describe(show_secrets))) }
+
+        Some(parts.join(", "))
+    }
+}
+
+// Parses a header given as 'Name: value'. The value is a secret's name for headers that come from the secrets file.
+pub fn parse_header(raw_header: &str) -> Result<Header>
+{
+    match raw_header.split_once(':')
+    {
+        Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.trim().to_string())),
+        _ => Err(Error::Invalid(format!("Invalid header '{}'. Headers look like 'Name: value'.", raw_header)))
+    }
+}
+
+
+// Wraps another client, adding a podcast's credentials to every request it makes. Private feeds need one of these for
+// fetching the feed and downloading episodes.
+pub struct AuthenticatedClient<'a>
+{
+    inner: &'a dyn HttpClient,
+    headers: Vec<Header>
+}
+
+impl<'a> AuthenticatedClient<'a>
+{
+    pub fn new(inner: &'a dyn HttpClient, credentials: &FeedCredentials, secrets: &SecretStore) -> Result<Self>
+    {
+        Ok(AuthenticatedClient { inner, headers: credentials.request_headers(secrets)? })
+    }
+
+    // The wrapped client, for requests that shouldn't carry the credentials, like files hosted by someone else.
+    pub fn without_credentials(&self) -> &'a dyn HttpClient
+    {
+        self.inner
+    }
+}
+
+impl HttpClient for AuthenticatedClient<'_>
+{
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse>
+    {
+        let mut authenticated_request = request.clone();
+        authenticated_request.headers.extend(self.headers.iter().cloned());
+        authenticated_request.credential_headers.extend(self.headers.iter().map(|(name, _)| name.clone()));
+
+        self.inner.get(&authenticated_request)
+    }
+}
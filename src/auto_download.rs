@@ -0,0 +1,193 @@
+// A podcast's auto-download rules, which 'update' and 'podclaw daemon' follow right after refreshing its cache. Only
+// episodes published since the rules were turned on count as new, unless 'since' reaches further back, so turning them
+// on doesn't download a whole back catalogue.
+
+use std::fs::remove_file;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, FixedOffset, Utc};
+use rss::Item;
+use serde::{Deserialize, Serialize};
+use crate::error::{Error, Result};
+use crate::feed_fields::{episode_id, episode_title};
+use crate::filters::{parse_title_regex, EpisodeFilter};
+use crate::http::HttpClient;
+use crate::manage_feeds::{download_file, episode_audio_url, episode_download_path, load_cached_feed, ordered_episodes, EpisodeOrder};
+use crate::structs::Podcast;
+
+// 'keep_latest' keeps only the newest matching episodes on disk, deleting older ones again, but only the ones these
+// rules downloaded, which 'downloaded' keeps track of by 'feed_fields::episode_id'.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AutoDownload
+{
+    pub enabled: bool,
+    pub keep_latest: Option<usize>,
+    pub include_pattern: Option<String>,
+    pub exclude_pattern: Option<String>,
+    pub min_duration: Option<Duration>,
+    pub max_duration: Option<Duration>,
+    pub since: Option<SystemTime>,
+    pub downloaded: Vec<String>
+}
+
+// Changes to a podcast's rules, for 'edit_podcast'. Fields left as 'None' keep their current value, and 'reset' clears
+// every rule before the others are applied.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AutoDownloadEdit
+{
+    pub enabled: Option<bool>,
+    pub keep_latest: Option<usize>,
+    pub include_pattern: Option<String>,
+    pub exclude_pattern: Option<String>,
+    pub min_duration: Option<Duration>,
+    pub max_duration: Option<Duration>,
+    pub since: Option<SystemTime>,
+    pub reset: bool
+}
+
+// Which episodes the rules want, by their index in the oldest-first order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AutoDownloadPlan
+{
+    pub download: Vec<usize>,
+    pub remove: Vec<usize>
+}
+
+// What following the rules did. A failed episode doesn't stop the others.
+#[derive(Debug, Default)]
+pub struct AutoDownloadReport
+{
+    pub downloaded: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    pub failures: Vec<(String, Error)>
+}
+
+impl AutoDownloadEdit
+{
+    pub fn is_empty(&self) -> bool
+    {
+        *self == AutoDownloadEdit::default()
+    }
+}
+
+impl AutoDownload
+{
+    // Bad patterns are turned away here, so they can't break every update afterwards.
+    pub fn apply(&mut self, edit: AutoDownloadEdit) -> Result<()>
+    {
+        for pattern in [&edit.include_pattern, &edit.exclude_pattern].into_iter().flatten() { parse_title_regex(pattern)?; }
+
+        if edit.reset { *self = AutoDownload { downloaded: std::mem::take(&mut self.downloaded), ..AutoDownload::default() } }
+        if let Some(enabled) = edit.enabled { self.enabled = enabled }
+        if let Some(keep_latest) = edit.keep_latest { self.keep_latest = Some(keep_latest).filter(|keep_latest| *keep_latest > 0) }
+        if edit.include_pattern.is_some() { self.include_pattern = edit.include_pattern.filter(|pattern| !pattern.is_empty()) }
+        if edit.exclude_pattern.is_some() { self.exclude_pattern = edit.exclude_pattern.filter(|pattern| !pattern.is_empty()) }
+        if edit.min_duration.is_some() { self.min_duration = edit.min_duration }
+        if edit.max_duration.is_some() { self.max_duration = edit.max_duration }
+        if edit.since.is_some() { self.since = edit.since }
+
+        if self.enabled && self.since.is_none() { self.since = Some(SystemTime::now()) }
+        Ok(())
+    }
+
+    // Lists the rules for 'inspect', or nothing when they're off.
+    pub fn describe(&self) -> Option<String>
+    {
+        if !self.enabled { return None }
+
+        let mut rules = Vec::new();
+        if let Some(since) = self.since { rules.push(format!("published since {}", DateTime::<Utc>::from(since).format("%Y-%m-%d"))) }
+        if let Some(pattern) = &self.include_pattern { rules.push(format!("titles matching '{}'", pattern)) }
+        if let Some(pattern) = &self.exclude_pattern { rules.push(format!("titles not matching '{}'", pattern)) }
+        if let Some(min_duration) = self.min_duration { rules.push(format!("at least {} minutes long", min_duration.as_secs() / 60)) }
+        if let Some(max_duration) = self.max_duration { rules.push(format!("at most {} minutes long", max_duration.as_secs() / 60)) }
+        if let Some(keep_latest) = self.keep_latest { rules.push(format!("keeping the latest {}", keep_latest)) }
+
+        Some(if rules.is_empty() { String::from("new episodes") } else { format!("new episodes {}", rules.join(", ")) })
+    }
+
+    // 'is_downloaded' tells whether an episode's file is already there. The newest matching episodes come last.
+    pub fn plan(&self, episodes: &[Item], is_downloaded: impl Fn(usize, &Item) -> bool) -> Result<AutoDownloadPlan>
+    {
+        if !self.enabled { return Ok(AutoDownloadPlan::default()) }
+
+        let filter = EpisodeFilter
+        {
+            since: self.since.map(|since| DateTime::<FixedOffset>::from(DateTime::<Utc>::from(since))),
+            min_duration: self.min_duration,
+            max_duration: self.max_duration,
+            title_regex: self.include_pattern.as_deref().map(parse_title_regex).transpose()?,
+            has_enclosure: true,
+            ..EpisodeFilter::default()
+        };
+        let exclude = self.exclude_pattern.as_deref().map(parse_title_regex).transpose()?;
+
+        let mut matching = filter.apply(episodes);
+        if let Some(exclude) = exclude
+        {
+            matching.retain(|index| !episode_title(&episodes[*index]).is_some_and(|title| exclude.is_match(&title)));
+        }
+
+        let kept_from = self.keep_latest.map_or(0, |keep_latest| matching.len().saturating_sub(keep_latest));
+        let (stale, kept) = matching.split_at(kept_from);
+
+        Ok(AutoDownloadPlan
+        {
+            download: kept.iter().copied().filter(|index| !is_downloaded(*index, &episodes[*index])).collect(),
+            remove: stale.iter().copied()
+                .filter(|index| self.downloaded.contains(&episode_id(&episodes[*index])) && is_downloaded(*index, &episodes[*index]))
+                .collect()
+        })
+    }
+}
+
+
+// Downloads what the podcast's rules want from its cached feed, and deletes what 'keep_latest' lets go of. Files are
+// named with 'template', like 'get' names them.
+pub fn run_auto_download(client: &dyn HttpClient, podcast: &mut Podcast, template: &str) -> Result<AutoDownloadReport>
+{
+    let mut report = AutoDownloadReport::default();
+    if !podcast.auto_download.enabled { return Ok(report) }
+
+    let feed = load_cached_feed(podcast)?;
+    let episodes = ordered_episodes(&feed, EpisodeOrder::Oldest);
+    let path_of = |index: usize, episode: &Item| episode_download_path(podcast, index, episode, template);
+    let plan = podcast.auto_download.plan(&episodes, |index, episode| path_of(index, episode).exists())?;
+
+    let mut downloaded_ids = Vec::new();
+    let mut removed_ids = Vec::new();
+
+    for index in plan.download
+    {
+        let path = path_of(index, &episodes[index]);
+        match episode_audio_url(&episodes[index]).and_then(|audio_url| download_file(client, &audio_url, &path))
+        {
+            Ok(()) =>
+            {
+                downloaded_ids.push(episode_id(&episodes[index]));
+                report.downloaded.push(path);
+            }
+            Err(error) => report.failures.push((path.display().to_string(), error))
+        }
+    }
+
+    for index in plan.remove
+    {
+        let path = path_of(index, &episodes[index]);
+        match remove_file(&path)
+        {
+            Ok(()) =>
+            {
+                removed_ids.push(episode_id(&episodes[index]));
+                report.removed.push(path);
+            }
+            Err(error) => report.failures.push((path.display().to_string(), Error::from(error)))
+        }
+    }
+
+    let rules = &mut podcast.auto_download;
+    rules.downloaded.retain(|id| !removed_ids.contains(id));
+    for id in downloaded_ids { if !rules.downloaded.contains(&id) { rules.downloaded.push(id) } }
+
+    Ok(report)
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::error::{Error, Result};
+use crate::http::{HttpSettings, NetworkOverrides};
+use crate::search::SearchSettings;
+use crate::theme::{ColorMode, Palette, ThemeSpec};
+use crate::utils::{get_config_dir, normalize_alias};
+
+pub const DEFAULT_INTERVAL: usize = 24;
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "[{alias} - {index}] {title}";
+
+// Podclaw's global settings, read from 'config.toml' next to the storage file. Every section is optional.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config
+{
+    pub defaults: Defaults,
+    pub output: OutputSettings,
+    pub network: NetworkOverrides,
+    pub search: SearchSettings,
+    pub tags: BTreeMap<String, TagDefaults>,
+    pub themes: BTreeMap<String, ThemeSpec>
+}
+
+// The '[defaults]' section. Without a download path, a new podcast's episodes go into a folder named after its alias
+// inside 'download_root'. The filename template can use '{alias}', '{index}', '{title}' and '{date}'.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Defaults
+{
+    pub download_root: Option<PathBuf>,
+    pub interval: Option<usize>,
+    pub filename_template: Option<String>,
+    pub concurrency: Option<usize>
+}
+
+// The '[output]' section. Without a palette, it's guessed from the terminal.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputSettings
+{
+    pub theme: Option<String>,
+    pub color: Option<ColorMode>,
+    pub palette: Option<Palette>
+}
+
+// A '[tags.<name>]' section. Podcasts added with that tag use these when they're not given their own.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TagDefaults
+{
+    pub download_path: Option<PathBuf>,
+    pub interval: Option<usize>
+}
+
+impl Config
+{
+    // The network settings for a podcast: its own overrides on top of the config, on top of the defaults.
+    pub fn http_settings(&self, podcast_overrides: &NetworkOverrides) -> HttpSettings
+    {
+        HttpSettings::default().with_overrides(&[&self.network, podcast_overrides])
+    }
+
+    // The folder that holds a folder for each podcast that isn't given a download path. A leading '~' is the home folder.
+    pub fn download_root(&self) -> Option<PathBuf>
+    {
+        let download_root = self.defaults.download_root.as_ref()?;
+
+        match (download_root.strip_prefix("~"), dirs::home_dir())
+        {
+            (Ok(relative_root), Some(home_dir)) => Some(home_dir.join(relative_root)),
+            _ => Some(download_root.clone())
+        }
+    }
+
+    pub fn interval(&self) -> usize
+    {
+        self.defaults.interval.unwrap_or(DEFAULT_INTERVAL)
+    }
+
+    pub fn filename_template(&self) -> &str
+    {
+        self.defaults.filename_template.as_deref().unwrap_or(DEFAULT_FILENAME_TEMPLATE)
+    }
+
+    // How many podcasts a batch works on at once. Zero is treated as one.
+    pub fn concurrency(&self) -> usize
+    {
+        self.defaults.concurrency.unwrap_or(1).max(1)
+    }
+
+    // Applies the 'PODCLAW_*' environment variables on top of the file. 'lookup' reads a variable, so tests don't need
+    // to touch the real environment.
+    pub fn apply_environment(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<()>
+    {
+        let number = |name: &str, value: String| value.trim().parse::<u64>()
+            .map_err(|_| Error::Invalid(format!("'{}' should be a whole number, not '{}'.", name, value)));
+
+        if let Some(value) = lookup("PODCLAW_DOWNLOAD_ROOT") { self.defaults.download_root = Some(PathBuf::from(value)) }
+        if let Some(value) = lookup("PODCLAW_INTERVAL") { self.defaults.interval = Some(number("PODCLAW_INTERVAL", value)? as usize) }
+        if let Some(value) = lookup("PODCLAW_FILENAME_TEMPLATE") { self.defaults.filename_template = Some(value) }
+        if let Some(value) = lookup("PODCLAW_CONCURRENCY") { self.defaults.concurrency = Some(number("PODCLAW_CONCURRENCY", value)? as usize) }
+        if let Some(value) = lookup("PODCLAW_THEME") { self.output.theme = Some(value) }
+        if let Some(value) = lookup("PODCLAW_COLOR") { self.output.color = Some(value.trim().parse()?) }
+        if let Some(value) = lookup("PODCLAW_USER_AGENT") { self.network.user_agent = Some(value) }
+        if let Some(value) = lookup("PODCLAW_PROXY") { self.network.proxy = Some(value) }
+        if let Some(value) = lookup("PODCLAW_RETRIES") { self.network.retries = Some(number("PODCLAW_RETRIES", value)? as u32) }
+        if let Some(value) = lookup("PODCLAW_RETRY_BACKOFF") { self.network.retry_backoff = Some(number("PODCLAW_RETRY_BACKOFF", value)?) }
+        if let Some(value) = lookup("PODCLAW_CONNECT_TIMEOUT") { self.network.connect_timeout = Some(number("PODCLAW_CONNECT_TIMEOUT", value)?) }
+        if let Some(value) = lookup("PODCLAW_READ_TIMEOUT") { self.network.read_timeout = Some(number("PODCLAW_READ_TIMEOUT", value)?) }
+
+        Ok(())
+    }
+
+    // The defaults for a podcast with these tags. When several tags set the same thing, the first one wins.
+    pub fn tag_defaults(&self, tags: &[String]) -> TagDefaults
+    {
+        let mut defaults = TagDefaults::default();
+
+        for tag in tags
+        {
+            let tag = normalize_alias(tag);
+            let Some((_, tag_defaults)) = self.tags.iter().find(|(name, _)| normalize_alias(name) == tag) else { continue };
+
+            if defaults.download_path.is_none() { defaults.download_path = tag_defaults.download_path.clone() }
+            if defaults.interval.is_none() { defaults.interval = tag_defaults.interval }
+        }
+
+        defaults
+    }
+}
+
+
+pub fn get_config_path() -> Result<PathBuf>
+{
+    Ok(get_config_dir()?.join("config.toml"))
+}
+
+// Loads the config file, then applies the environment variables on top. A missing file just means every setting keeps
+// its default.
+pub fn load_config() -> Result<Config>
+{
+    let config_path = get_config_path()?;
+
+    let mut config = if config_path.try_exists()?
+    {
+        let raw_config = read_to_string(&config_path)?;
+        parse_config(&raw_config)
+            .map_err(|error| Error::Parse(format!("Failed to parse '{}': {}", config_path.display(), error)))?
+    }
+    else { Config::default() };
+
+    config.apply_environment(|name| std::env::var(name).ok())?;
+    Ok(config)
+}
+
+pub fn parse_config(raw_config: &str) -> Result<Config>
+{
+    toml::from_str(raw_config).map_err(|error| Error::Parse(error.message().to_string()))
+}
@@ -0,0 +1,211 @@
+// 'podclaw daemon' keeps podcasts' caches fresh in the foreground, which suits running it as a systemd service. It
+// refreshes each podcast when its update interval is up, picks up changes to the storage and config files on its own,
+// and stops cleanly between refreshes on SIGTERM or Ctrl+C.
+
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, Local};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use podclaw::{Error, Result};
+use podclaw::structs::Podcast;
+use podclaw::config::{get_config_path, Config};
+use podclaw::manage_feeds::update_podcast;
+use podclaw::auto_download::run_auto_download;
+use podclaw::scheduler::{FileWatcher, Scheduler};
+use podclaw::utils::{get_storage, get_storage_path, save_storage};
+use crate::{authenticated, client_for, LazySecrets};
+
+// How often the storage and config files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Sleeping happens in short steps, so a signal is noticed quickly.
+const SLEEP_STEP: Duration = Duration::from_millis(200);
+
+// Writes timestamped lines to stderr, or appends them to a file.
+struct DaemonLog
+{
+    file: Option<File>
+}
+
+impl DaemonLog
+{
+    fn open(path: Option<PathBuf>) -> Result<Self>
+    {
+        let file = path.map(|path| OpenOptions::new().create(true).append(true).open(&path)
+            .map_err(|error| Error::Storage(format!("Failed to open the log file '{}': {}", path.display(), error))))
+            .transpose()?;
+
+        Ok(DaemonLog { file })
+    }
+
+    fn write(&mut self, message: impl Display)
+    {
+        let line = format!("{} {}", Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+        match &mut self.file
+        {
+            // A full disk shouldn't stop the refreshes, so failed writes fall back to stderr.
+            Some(file) => if writeln!(file, "{}", line).is_err() { eprintln!("{}", line) },
+            None => eprintln!("{}", line)
+        }
+    }
+}
+
+// The config is reloaded with 'reload_config', so options given on the command line still apply afterwards.
+pub fn run_daemon(mut config: Config, reload_config: impl Fn() -> Result<Config>, log_path: Option<PathBuf>) -> Result<()>
+{
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGINT] { signal_hook::flag::register(signal, Arc::clone(&shutdown))?; }
+
+    let mut log = DaemonLog::open(log_path)?;
+    let mut storage_watcher = FileWatcher::new(get_storage_path()?);
+    let mut config_watcher = FileWatcher::new(get_config_path()?);
+    let mut podcasts = get_storage()?;
+    let mut scheduler = Scheduler::default();
+
+    // Unlocking the secrets now means asking for the passphrase once, while someone is likely watching.
+    let secrets = LazySecrets::default();
+    secrets.unlock_for(&podcasts, &(0..podcasts.len()).collect::<Vec<usize>>())?;
+
+    log.write(format!("Started, watching {} podcasts.", podcasts.len()));
+
+    while !shutdown.load(Ordering::Relaxed)
+    {
+        if storage_watcher.has_changed()
+        {
+            match get_storage()
+            {
+                Ok(reloaded) =>
+                {
+                    podcasts = reloaded;
+                    log.write(format!("The storage changed, now watching {} podcasts.", podcasts.len()));
+                }
+
+                Err(error) => log.write(format!("Failed to reload the storage, keeping the old one: {}", error))
+            }
+        }
+
+        if config_watcher.has_changed()
+        {
+            match reload_config()
+            {
+                Ok(reloaded) =>
+                {
+                    config = reloaded;
+                    log.write("Reloaded the config.");
+                }
+
+                Err(error) => log.write(format!("Failed to reload the config, keeping the old one: {}", error))
+            }
+        }
+
+        scheduler.sync(&podcasts, SystemTime::now());
+        let due: Vec<String> = scheduler.due(&podcasts, SystemTime::now()).into_iter().map(|index| podcasts[index].alias.clone()).collect();
+
+        for alias in due
+        {
+            if shutdown.load(Ordering::Relaxed) { break }
+            let Some(index) = podcasts.iter().position(|podcast| podcast.alias == alias) else { continue };
+
+            let mut refreshed = podcasts[index].clone();
+            let result = refresh(&config, &secrets, &mut refreshed)
+                .and_then(|message| save_refresh(&mut podcasts, &refreshed, &mut storage_watcher).map(|_| message));
+
+            match result
+            {
+                Ok(message) =>
+                {
+                    scheduler.sync(&podcasts, SystemTime::now());
+                    let next = scheduler.due_at(&alias).map_or_else(String::new, |due| format!(" Next refresh at {}.", format_time(due)));
+                    log.write(format!("{}{}", message, next));
+                }
+
+                Err(error) =>
+                {
+                    if let Some(podcast) = podcasts.iter().find(|podcast| podcast.alias == alias) { scheduler.postpone(podcast, SystemTime::now()) }
+                    let next = scheduler.due_at(&alias).map_or_else(String::new, |due| format!(" Trying again at {}.", format_time(due)));
+                    log.write(format!("Failed to refresh '{}': {}{}", alias, error, next));
+                }
+            }
+        }
+
+        wait_until(scheduler.next_due(), &shutdown);
+    }
+
+    log.write("Stopped.");
+    Ok(())
+}
+
+
+fn refresh(config: &Config, secrets: &LazySecrets, podcast: &mut Podcast) -> Result<String>
+{
+    let base_client = client_for(config, &podcast.network)?;
+    let client = authenticated(&base_client, &podcast.credentials, secrets)?;
+    let feed_move = update_podcast(&client, podcast)?;
+
+    let mut message = match feed_move
+    {
+        Some(feed_move) => format!("Refreshed '{}', whose feed moved ({}) from '{}' to '{}'.", podcast.alias, feed_move.reason.description(), feed_move.old_url, feed_move.new_url),
+        None => format!("Refreshed '{}'.", podcast.alias)
+    };
+
+    // The refresh itself worked, so a failed auto-download is only mentioned.
+    match run_auto_download(&client, podcast, config.filename_template())
+    {
+        Ok(report) =>
+        {
+            for path in &report.downloaded { message += &format!(" Downloaded '{}'.", path.display()) }
+            for path in &report.removed { message += &format!(" Deleted '{}'.", path.display()) }
+            for (path, error) in &report.failures { message += &format!(" Auto-download failed for '{}': {}", path, error) }
+        }
+
+        Err(error) => message += &format!(" Auto-download failed: {}", error)
+    }
+
+    Ok(message)
+}
+
+// Other commands can change the storage while a feed is being fetched, so the refresh is copied into the newest storage
+// instead of saving over it. A podcast that was removed in the meantime stays removed.
+fn save_refresh(podcasts: &mut Vec<Podcast>, refreshed: &Podcast, storage_watcher: &mut FileWatcher) -> Result<()>
+{
+    if storage_watcher.has_changed() { *podcasts = get_storage()? }
+    let Some(podcast) = podcasts.iter_mut().find(|podcast| podcast.alias == refreshed.alias) else { return Ok(()) };
+
+    podcast.cache_content = refreshed.cache_content.clone();
+    podcast.cache_time = refreshed.cache_time;
+    podcast.feed_url = refreshed.feed_url.clone();
+    podcast.previous_feed_urls = refreshed.previous_feed_urls.clone();
+
+    // Only what auto-download did is copied, so rules edited meanwhile are kept.
+    podcast.auto_download.downloaded = refreshed.auto_download.downloaded.clone();
+    save_storage(podcasts)?;
+
+    // The daemon's own save isn't a change it needs to reload.
+    storage_watcher.has_changed();
+    Ok(())
+}
+
+// Sleeps until the next podcast is due, but wakes up to check the files every 'POLL_INTERVAL', and right away on a signal.
+fn wait_until(next_due: Option<SystemTime>, shutdown: &AtomicBool)
+{
+    let started = SystemTime::now();
+    let wait = next_due.map_or(POLL_INTERVAL, |due| due.duration_since(started).unwrap_or_default()).min(POLL_INTERVAL);
+
+    while !shutdown.load(Ordering::Relaxed)
+    {
+        let waited = started.elapsed().unwrap_or(wait);
+        if waited >= wait { break }
+        sleep(SLEEP_STEP.min(wait - waited));
+    }
+}
+
+fn format_time(time: SystemTime) -> String
+{
+    DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string()
+}
@@ -0,0 +1,132 @@
+// Finds the feeds a web page links to, so a show's homepage can be used instead of its feed URL. Sites announce their
+// feeds with tags like '<link rel="alternate" type="application/rss+xml" href="/feed.xml">'.
+
+// Picks one of several candidates by index, or none of them.
+pub type FeedChooser = fn(&[FeedCandidate]) -> Option<usize>;
+
+// A feed linked from a page. The URL is absolute.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedCandidate
+{
+    pub url: String,
+    pub title: Option<String>,
+    pub is_atom: bool
+}
+
+impl FeedCandidate
+{
+    pub fn description(&self) -> String
+    {
+        let kind = if self.is_atom { "Atom" } else { "RSS" };
+
+        match &self.title
+        {
+            Some(title) => format!("{} ({}, {})", title, kind, self.url),
+            None => format!("{} ({})", self.url, kind)
+        }
+    }
+}
+
+// Every RSS and Atom feed a page links to, RSS first, since that's what Podclaw can read. Relative links are resolved
+// against the page's URL.
+pub fn discover_feeds(html: &str, page_url: &str) -> Vec<FeedCandidate>
+{
+    let base_url = reqwest::Url::parse(page_url).ok();
+    let mut candidates: Vec<FeedCandidate> = Vec::new();
+
+    for tag in link_tags(html)
+    {
+        let attributes = parse_attributes(tag);
+        let attribute = |name: &str| attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+
+        let is_alternate = attribute("rel").is_some_and(|rel| rel.split_whitespace().any(|token| token.eq_ignore_ascii_case("alternate")));
+        let is_atom = match attribute("type").map(|kind| kind.trim().to_lowercase()).as_deref()
+        {
+            Some("application/rss+xml") => false,
+            Some("application/atom+xml") => true,
+            _ => continue
+        };
+
+        let Some(href) = attribute("href").filter(|href| is_alternate && !href.trim().is_empty()) else { continue };
+        let url = match &base_url
+        {
+            Some(base_url) => match base_url.join(href.trim()) { Ok(url) => url.to_string(), Err(_) => continue },
+            None => href.trim().to_string()
+        };
+
+        if candidates.iter().any(|candidate| candidate.url == url) { continue }
+
+        let title = attribute("title").map(str::trim).filter(|title| !title.is_empty()).map(str::to_string);
+        candidates.push(FeedCandidate { url, title, is_atom });
+    }
+
+    candidates.sort_by_key(|candidate| candidate.is_atom);
+    candidates
+}
+
+// The inside of every '<link ...>' tag, without the tag name and brackets.
+fn link_tags(html: &str) -> Vec<&str>
+{
+    let lowercase_html = html.to_ascii_lowercase();
+    let mut tags = Vec::new();
+    let mut position = 0;
+
+    while let Some(offset) = lowercase_html[position..].find("<link")
+    {
+        let start = position + offset + "<link".len();
+        let Some(length) = html[start..].find('>') else { break };
+
+        // '<linkage>' and similar aren't link tags.
+        if html[start..].starts_with(|character: char| character.is_whitespace()) { tags.push(&html[start..start + length]) }
+        position = start + length;
+    }
+
+    tags
+}
+
+// Reads 'name="value"' pairs, allowing single quotes, no quotes, and attributes without values. Names are lowercased.
+fn parse_attributes(tag: &str) -> Vec<(String, String)>
+{
+    let mut attributes = Vec::new();
+    let mut rest = tag.trim_start().trim_end_matches('/');
+
+    while !rest.is_empty()
+    {
+        let name_length = rest.find(|character: char| character == '=' || character.is_whitespace()).unwrap_or(rest.len());
+        let name = rest[..name_length].to_lowercase();
+        rest = rest[name_length..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after_equals) = rest.strip_prefix('=')
+        {
+            let after_equals = after_equals.trim_start();
+            let (raw_value, remainder) = match after_equals.chars().next()
+            {
+                Some(quote @ ('"' | '\'')) =>
+                {
+                    let closing = after_equals[1..].find(quote).map(|index| index + 1).unwrap_or(after_equals.len());
+                    (&after_equals[1..closing], after_equals.get(closing + 1..).unwrap_or(""))
+                }
+                _ =>
+                {
+                    let end = after_equals.find(char::is_whitespace).unwrap_or(after_equals.len());
+                    (&after_equals[..end], &after_equals[end..])
+                }
+            };
+
+            value = decode_entities(raw_value);
+            rest = remainder;
+        }
+
+        if !name.is_empty() { attributes.push((name, value)) }
+        rest = rest.trim_start();
+    }
+
+    attributes
+}
+
+// Only the entities that show up in URLs and titles in practice.
+fn decode_entities(value: &str) -> String
+{
+    value.replace("&quot;", "\"").replace("&#39;", "'").replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
@@ -0,0 +1,378 @@
+use podclaw::Result;
+use podclaw::auth::FeedCredentials;
+use podclaw::manage_feeds::FeedMove;
+use podclaw::search::SearchResult;
+use podclaw::schedule::{describe_every, ScheduleStatus};
+use podclaw::config::Config;
+use podclaw::structs::{Podcast, TextDeco};
+use podclaw::theme::{build_text_deco, detect_palette, use_colors, Palette};
+use podclaw::records::OutputFormat;
+use podclaw::podcast_namespace::*;
+use podclaw::feed_fields::*;
+use podclaw::utils::*;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use std::io::{stderr, stdout, IsTerminal};
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use chrono::{DateTime, Local};
+use rss::{Channel, Item};
+use serde::Serialize;
+
+// The theme is picked once, from the config, before anything is printed. Anything printed before that, like an error
+// in the config itself, uses the default theme.
+static ACTIVE_THEME: OnceLock<(TextDeco, bool)> = OnceLock::new();
+
+// Set once from '--output', before anything is printed.
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+// The records of a '--output json' stream, printed together once the command is done.
+static COLLECTED_RECORDS: Mutex<Vec<serde_json::Value>> = Mutex::new(Vec::new());
+
+// Whether the command printed its own document, so 'finish_records' doesn't add a second one.
+static PRINTED_DOCUMENT: AtomicBool = AtomicBool::new(false);
+
+pub static TXTD: LazyLock<TextDeco> = LazyLock::new(||
+{
+    match ACTIVE_THEME.get()
+    {
+        Some((text_deco, _)) => text_deco.clone(),
+        None => build_text_deco("default", &BTreeMap::new(), output_palette(&Config::default())).unwrap_or_default()
+    }
+});
+
+pub fn use_theme(config: &Config) -> Result<()>
+{
+    let theme_name = config.output.theme.as_deref().unwrap_or("default");
+    let palette = output_palette(config);
+    let text_deco = build_text_deco(theme_name, &config.themes, palette)?;
+
+    let _ = ACTIVE_THEME.set((text_deco, palette.is_some() && theme_name != "plain"));
+    Ok(())
+}
+
+// The palette to write colors for, or none if there shouldn't be any. With structured output, the text goes to
+// stderr, so that's the one that has to be a terminal.
+fn output_palette(config: &Config) -> Option<Palette>
+{
+    let lookup = |name: &str| std::env::var(name).ok();
+    let is_terminal = if is_structured_output() { stderr().is_terminal() } else { stdout().is_terminal() };
+    if !use_colors(config.output.color.unwrap_or_default(), is_terminal, lookup) { return None }
+
+    Some(config.output.palette.unwrap_or_else(|| detect_palette(lookup)))
+}
+
+// Whether italics and bold are shown. They're off whenever colors are, and in the plain theme.
+pub fn is_decorated() -> bool
+{
+    ACTIVE_THEME.get().map_or(output_palette(&Config::default()).is_some(), |(_, is_decorated)| *is_decorated)
+}
+
+pub fn use_output_format(format: OutputFormat)
+{
+    let _ = OUTPUT_FORMAT.set(format);
+}
+
+pub fn output_format() -> OutputFormat
+{
+    OUTPUT_FORMAT.get().copied().unwrap_or_default()
+}
+
+pub fn is_structured_output() -> bool
+{
+    output_format() != OutputFormat::Text
+}
+
+// Prints a whole document, like the podcasts of 'list': a JSON array, or one line per record with ndjson.
+pub fn print_records<T: Serialize>(records: &[T])
+{
+    match output_format()
+    {
+        OutputFormat::Text => {}
+        OutputFormat::Json =>
+        {
+            println!("{}", to_json(records, true));
+            PRINTED_DOCUMENT.store(true, Ordering::Relaxed);
+        }
+        OutputFormat::Ndjson => for record in records { println!("{}", to_json(record, false)) }
+    }
+}
+
+// Prints a document that's a single record, like an inspected episode.
+pub fn print_record<T: Serialize>(record: &T)
+{
+    match output_format()
+    {
+        OutputFormat::Text => {}
+        OutputFormat::Json =>
+        {
+            println!("{}", to_json(record, true));
+            PRINTED_DOCUMENT.store(true, Ordering::Relaxed);
+        }
+        OutputFormat::Ndjson => println!("{}", to_json(record, false))
+    }
+}
+
+// Adds a record to the command's stream. Ndjson prints it right away, and JSON waits for 'finish_records'.
+pub fn emit_record<T: Serialize>(record: &T)
+{
+    match output_format()
+    {
+        OutputFormat::Text => {}
+        OutputFormat::Json => COLLECTED_RECORDS.lock().unwrap().push(serde_json::to_value(record).unwrap_or_default()),
+        OutputFormat::Ndjson => println!("{}", to_json(record, false))
+    }
+}
+
+// JSON output is always a document, so a command that neither printed nor collected anything still prints '[]'.
+pub fn finish_records()
+{
+    let records = std::mem::take(&mut *COLLECTED_RECORDS.lock().unwrap());
+    let is_needed = !records.is_empty() || (output_format() == OutputFormat::Json && !PRINTED_DOCUMENT.load(Ordering::Relaxed));
+    if is_needed { println!("{}", to_json(&records, true)) }
+}
+
+// Records are plain strings, numbers and lists, which always serialize.
+fn to_json<T: Serialize + ?Sized>(value: &T, pretty: bool) -> String
+{
+    let json = if pretty { serde_json::to_string_pretty(value) } else { serde_json::to_string(value) };
+    json.unwrap_or_default()
+}
+
+// Human-readable output. With structured output, stdout is kept for the records, so this goes to stderr instead.
+macro_rules! report
+{
+    ($($arguments:tt)*) =>
+    {
+        if crate::display::is_structured_output() { eprintln!($($arguments)*) } else { println!($($arguments)*) }
+    }
+}
+
+
+macro_rules! italicize
+{
+    ($string:expr) =>
+    {
+        if crate::display::is_decorated() { format!("\x1b[3m{}\x1b[0m", $string) } else { format!("{}", $string) }
+    }
+}
+
+
+macro_rules! make_bold
+{
+    ($string:expr) =>
+    {
+        if crate::display::is_decorated() { format!("\x1b[1m{}\x1b[0m", $string) } else { format!("{}", $string) }
+    }
+}
+
+
+pub fn print_episode(feed: &Channel, episode_index: usize, target_episode: &Item)
+{
+    report!("{} Displaying details for the requested episode...", TXTD.general);
+
+    report!("  {} {}\n", make_bold!("Name: "), italicize!(or_missing(episode_title(target_episode))));
+    report!("  {} {}\n", make_bold!("Index: "), italicize!(format!("{}", episode_index)));
+    report!("  {} {}\n", make_bold!("Creator(s): "), italicize!(or_missing(episode_author(target_episode, feed))));
+    report!("  {} {}\n", make_bold!("Description: "), italicize!(or_missing(episode_description(target_episode))));
+
+    match episode_enclosure(target_episode)
+    {
+        Some(enclosure) => report!("  {} {}", make_bold!("Link: "), italicize!(format!("\'{}\'", enclosure.url ))),
+        None => report!("  {} {}", make_bold!("Link: "), italicize!("(none, this episode can't be downloaded)"))
+    }
+
+    let namespace = parse_item_namespace(target_episode, &namespace_prefix(feed));
+    let itunes = target_episode.itunes_ext.clone().unwrap_or_default();
+
+    if let Some(pub_date) = &target_episode.pub_date
+    {
+        print_detail("Published:", format_local_date(pub_date));
+    }
+
+    if let Some(duration) = itunes.duration.as_deref().and_then(parse_duration)
+    {
+        print_detail("Duration:", format_duration(duration));
+    }
+
+    if let Some(enclosure) = episode_enclosure(target_episode)
+    {
+        if let Ok(length) = enclosure.length.parse::<u64>()
+        {
+            if length > 0 { print_detail("Size:", format_bytes(length)) }
+        }
+
+        print_detail("Type:", enclosure.mime_type.clone());
+    }
+
+    // Podcasting 2.0 numbering wins over iTunes numbering, since it can carry a display name.
+    let season = match &namespace.season
+    {
+        Some(season) => Some(match &season.name
+        {
+            Some(name) => format!("{} ({})", season.number, name),
+            None => season.number.clone()
+        }),
+        None => itunes.season.clone()
+    };
+    if let Some(season) = season { print_detail("Season:", season) }
+
+    let episode = match &namespace.episode
+    {
+        Some(episode) => Some(episode.display.clone().unwrap_or(episode.number.clone())),
+        None => itunes.episode.clone()
+    };
+    if let Some(episode) = episode { print_detail("Episode:", episode) }
+
+    if let Some(explicit) = episode_explicit(target_episode, feed)
+    {
+        print_detail("Explicit:", explicit);
+    }
+
+    if let Some(guid) = &target_episode.guid
+    {
+        print_detail("GUID:", guid.value.clone());
+    }
+
+    let categories: Vec<String> = target_episode.categories.iter().map(|category| category.name.clone()).collect();
+    if !categories.is_empty() { print_detail("Categories:", categories.join(", ")) }
+
+    print_namespace(&namespace);
+}
+
+pub fn print_series(feed: &Channel)
+{
+    report!("{} Displaying details for the requested series...", TXTD.general);
+
+    report!("  {} {}\n", make_bold!("Name: "), italicize!(or_missing(channel_title(feed))));
+    report!("  {} {}\n", make_bold!("Creator(s): "), italicize!(or_missing(channel_author(feed))));
+    report!("  {} {}", make_bold!("Description: "), italicize!(or_missing(channel_description(feed))));
+
+    if let Some(language) = &feed.language
+    {
+        print_detail("Language:", language.clone());
+    }
+
+    let categories = channel_categories(feed);
+    if let Some(explicit) = channel_explicit(feed) { print_detail("Explicit:", explicit) }
+    if !categories.is_empty() { print_detail("Categories:", categories.join(", ")) }
+
+    print_detail("Episodes:", feed.items.len().to_string());
+
+    let latest_date = feed.items.iter()
+        .filter_map(|item| item.pub_date.as_deref().and_then(parse_pub_date))
+        .max();
+    if let Some(latest_date) = latest_date
+    {
+        print_detail("Latest Episode:", format_local_date(&latest_date.to_rfc2822()));
+    }
+
+    print_namespace(&parse_channel_namespace(feed));
+}
+
+// Secrets are hidden unless the user explicitly asks to see them.
+pub fn print_credentials(credentials: &FeedCredentials, show_secrets: bool)
+{
+    if let Some(description) = credentials.describe(show_secrets) { print_detail("Authentication:", description) }
+}
+
+pub fn print_feed_history(podcast: &Podcast)
+{
+    for previous in podcast.previous_feed_urls.iter().rev()
+    {
+        let replaced_at = DateTime::<Local>::from(previous.replaced_at).format("%Y-%m-%d %H:%M");
+        print_detail("Previous link:", format!("{} (until {}, {})", previous.url, replaced_at, previous.reason.description()));
+    }
+}
+
+pub fn print_feed_move(feed_move: &FeedMove)
+{
+    report!("{} This feed has moved ({}) from {} to {}.", TXTD.important, feed_move.reason.description(),
+        italicize!(format!("\'{}\'", feed_move.old_url)), italicize!(format!("\'{}\'", feed_move.new_url)));
+    report!("{} Podclaw will use the new link from now on. The old one is kept in the podcast's history.", TXTD.important);
+}
+
+pub fn print_search_results(results: &[SearchResult])
+{
+    if results.is_empty() { return report!("{} No podcasts found.", TXTD.important) }
+
+    report!("{} Found these podcasts...", TXTD.general);
+    for (result_index, result) in results.iter().enumerate()
+    {
+        let author = result.author.as_ref().map(|author| format!(" by {}", author)).unwrap_or_default();
+        report!("  {} {}{}", make_bold!(format!("#{}:", result_index)), italicize!(format!("\'{}\'", result.title)), author);
+        report!("      {}", result.feed_url);
+    }
+}
+
+pub fn print_schedule_status(status: &ScheduleStatus)
+{
+    if !status.systemd_installed && status.cron_line.is_none() { return report!("{} No schedule is installed.", TXTD.important) }
+
+    if status.systemd_installed
+    {
+        let every = status.systemd_every_seconds.map_or_else(|| String::from(MISSING), |seconds| describe_every(Duration::from_secs(seconds)));
+        let state = match status.timer_active
+        {
+            Some(true) => "active",
+            Some(false) => "inactive",
+            None => "unknown"
+        };
+
+        report!("{} A systemd timer updates the podcasts every {}.", TXTD.general, italicize!(every));
+        print_detail("Units:", status.unit_dir.display().to_string());
+        print_detail("Timer:", state.to_string());
+    }
+
+    if let Some(line) = &status.cron_line
+    {
+        report!("{} A crontab line updates the podcasts.", TXTD.general);
+        print_detail("Line:", line.clone());
+    }
+}
+
+pub fn print_tags(podcast: &Podcast)
+{
+    if !podcast.tags.is_empty() { print_detail("Tags:", podcast.tags.join(", ")) }
+}
+
+pub fn print_auto_download(podcast: &Podcast)
+{
+    if let Some(rules) = podcast.auto_download.describe() { print_detail("Auto-download:", rules) }
+}
+
+fn print_detail(label: &str, value: String)
+{
+    report!("\n  {} {}", make_bold!(format!("{} ", label)), italicize!(format!("\'{}\'", value)));
+}
+
+// Prints whichever Podcasting 2.0 tags are present, skipping the rest. Seasons and episodes are handled alongside their iTunes counterparts.
+fn print_namespace(namespace: &PodcastNamespace)
+{
+    if let Some(chapters) = &namespace.chapters
+    {
+        print_detail("Chapters:", format!("{} ({})", chapters.url, chapters.mime_type));
+    }
+
+    for transcript in &namespace.transcripts
+    {
+        let details: Vec<String> = [Some(transcript.mime_type.clone()), transcript.language.clone(), transcript.rel.clone()]
+            .into_iter().flatten().filter(|detail| !detail.is_empty()).collect();
+        print_detail("Transcript:", format!("{} ({})", transcript.url, details.join(", ")));
+    }
+
+    for person in &namespace.persons
+    {
+        let mut role = person.role.clone().unwrap_or(String::from("host"));
+        if let Some(group) = &person.group { role = format!("{}, {}", group, role) }
+
+        let link = person.href.clone().map(|href| format!(" <{}>", href)).unwrap_or_default();
+        print_detail("Person:", format!("{} ({}){}", person.name, role, link));
+    }
+
+    for funding in &namespace.funding
+    {
+        let text = funding.text.clone().map(|text| format!("{}: ", text)).unwrap_or_default();
+        print_detail("Funding:", format!("{}{}", text, funding.url));
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt::{Display, Formatter};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// Every way a Podclaw operation can fail. The messages are written to be shown to the user as-is.
+#[derive(Debug)]
+pub enum Error
+{
+    // A request couldn't be made, or the server didn't answer with something usable.
+    Network(String),
+
+    // A feed or other downloaded document couldn't be understood.
+    Parse(String),
+
+    // The storage file, or a downloaded file, couldn't be read or written.
+    Storage(String),
+
+    // A podcast, episode, or other item doesn't exist.
+    NotFound(String),
+
+    // The podcast is locked, so it can't be changed.
+    Locked(String),
+
+    // The request itself doesn't make sense, like reusing an alias.
+    Invalid(String)
+}
+
+impl Display for Error
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            Error::Network(message) | Error::Parse(message) | Error::Storage(message)
+            | Error::NotFound(message) | Error::Locked(message) | Error::Invalid(message) => write!(f, "{}", message)
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error
+{
+    fn from(error: std::io::Error) -> Self
+    {
+        Error::Storage(format!("File access failed: {}", error))
+    }
+}
+
+impl From<bincode::Error> for Error
+{
+    fn from(error: bincode::Error) -> Self
+    {
+        Error::Storage(format!("Storage seems invalid ({}). Try running the 'repair' command!", error))
+    }
+}
+
+impl From<rss::Error> for Error
+{
+    fn from(error: rss::Error) -> Self
+    {
+        Error::Parse(format!("Failed to parse RSS feed: {}", error))
+    }
+}
+
+impl Error
+{
+    // The same kind of error with another message, so a summary keeps the exit code of what went wrong.
+    pub fn with_message(&self, message: String) -> Error
+    {
+        match self
+        {
+            Error::Network(_) => Error::Network(message),
+            Error::Parse(_) => Error::Parse(message),
+            Error::Storage(_) => Error::Storage(message),
+            Error::NotFound(_) => Error::NotFound(message),
+            Error::Locked(_) => Error::Locked(message),
+            Error::Invalid(_) => Error::Invalid(message)
+        }
+    }
+
+    // A short name for the kind of error, used in structured output.
+    pub fn kind(&self) -> &'static str
+    {
+        match self
+        {
+            Error::Network(_) => "network",
+            Error::Parse(_) => "parse",
+            Error::Storage(_) => "storage",
+            Error::NotFound(_) => "not_found",
+            Error::Locked(_) => "locked",
+            Error::Invalid(_) => "invalid"
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use rss::{Channel, Enclosure, Item};
+
+// Shown wherever a feed leaves out a field, so gaps are obvious instead of silently blank.
+pub const MISSING: &str = "(not provided)";
+
+// Most RSS fields are optional, and plenty of real feeds leave out the common ones. These helpers try the usual
+// alternatives (iTunes and Dublin Core tags, then the channel itself) before giving up.
+
+pub fn channel_title(channel: &Channel) -> Option<String>
+{
+    non_empty(Some(&channel.title))
+        .or_else(|| non_empty(channel.dublin_core_ext.as_ref().and_then(|dublin_core| dublin_core.titles.first())))
+}
+
+pub fn channel_description(channel: &Channel) -> Option<String>
+{
+    non_empty(Some(&channel.description))
+        .or_else(|| non_empty(channel.itunes_ext.as_ref().and_then(|itunes| itunes.summary.as_ref())))
+        .or_else(|| non_empty(channel.itunes_ext.as_ref().and_then(|itunes| itunes.subtitle.as_ref())))
+}
+
+pub fn channel_author(channel: &Channel) -> Option<String>
+{
+    non_empty(channel.itunes_ext.as_ref().and_then(|itunes| itunes.author.as_ref()))
+        .or_else(|| non_empty(channel.itunes_ext.as_ref().and_then(|itunes| itunes.owner.as_ref()).and_then(|owner| owner.name.as_ref())))
+        .or_else(|| non_empty(channel.dublin_core_ext.as_ref().and_then(|dublin_core| dublin_core.creators.first())))
+        .or_else(|| non_empty(channel.managing_editor.as_ref()))
+}
+
+pub fn channel_explicit(channel: &Channel) -> Option<String>
+{
+    non_empty(channel.itunes_ext.as_ref().and_then(|itunes| itunes.explicit.as_ref()))
+}
+
+// Plain RSS categories, then iTunes ones, which can have a subcategory.
+pub fn channel_categories(channel: &Channel) -> Vec<String>
+{
+    let mut categories: Vec<String> = channel.categories.iter().map(|category| category.name.clone()).collect();
+    if let Some(itunes) = &channel.itunes_ext
+    {
+        for category in &itunes.categories
+        {
+            match &category.subcategory
+            {
+                Some(subcategory) => categories.push(format!("{} > {}", category.text, subcategory.text)),
+                None => categories.push(category.text.clone())
+            }
+        }
+    }
+
+    categories
+}
+
+pub fn episode_title(item: &Item) -> Option<String>
+{
+    non_empty(item.title.as_ref())
+        .or_else(|| non_empty(item.itunes_ext.as_ref().and_then(|itunes| itunes.subtitle.as_ref())))
+        .or_else(|| non_empty(item.dublin_core_ext.as_ref().and_then(|dublin_core| dublin_core.titles.first())))
+}
+
+pub fn episode_description(item: &Item) -> Option<String>
+{
+    non_empty(item.description.as_ref())
+        .or_else(|| non_empty(item.itunes_ext.as_ref().and_then(|itunes| itunes.summary.as_ref())))
+        .or_else(|| non_empty(item.content.as_ref()))
+        .or_else(|| non_empty(item.itunes_ext.as_ref().and_then(|itunes| itunes.subtitle.as_ref())))
+}
+
+pub fn episode_author(item: &Item, channel: &Channel) -> Option<String>
+{
+    non_empty(item.itunes_ext.as_ref().and_then(|itunes| itunes.author.as_ref()))
+        .or_else(|| non_empty(item.dublin_core_ext.as_ref().and_then(|dublin_core| dublin_core.creators.first())))
+        .or_else(|| non_empty(item.author.as_ref()))
+        .or_else(|| channel_author(channel))
+}
+
+pub fn episode_explicit(item: &Item, channel: &Channel) -> Option<String>
+{
+    non_empty(item.itunes_ext.as_ref().and_then(|itunes| itunes.explicit.as_ref()))
+        .or_else(|| channel_explicit(channel))
+}
+
+// Text-only items have no enclosure, and some feeds ship enclosures with blank URLs. Neither can be downloaded.
+pub fn episode_enclosure(item: &Item) -> Option<&Enclosure>
+{
+    item.enclosure.as_ref().filter(|enclosure| !enclosure.url.trim().is_empty())
+}
+
+// Identifies an episode across feed updates, even when new episodes shift its index. The GUID is meant for exactly
+// that, and the enclosure URL or title stand in for feeds that leave it out.
+pub fn episode_id(item: &Item) -> String
+{
+    non_empty(item.guid.as_ref().map(|guid| &guid.value))
+        .or_else(|| episode_enclosure(item).map(|enclosure| enclosure.url.clone()))
+        .or_else(|| episode_title(item))
+        .unwrap_or_default()
+}
+
+// Used for file names, so it's never empty and never contains a path separator.
+pub fn episode_file_title(item: &Item) -> String
+{
+    let title = episode_title(item)
+        .or_else(|| item.guid.as_ref().map(|guid| guid.value.clone()))
+        .unwrap_or(String::from("Untitled"));
+
+    title.chars()
+        .map(|character| if matches!(character, '/' | '\\' | ':' | '\0') { '_' } else { character })
+        .collect()
+}
+
+// Quotes a value like the rest of Podclaw's output, or marks it as missing.
+pub fn or_missing(value: Option<String>) -> String
+{
+    match value
+    {
+        Some(value) => format!("\'{}\'", value),
+        None => String::from(MISSING)
+    }
+}
+
+
+fn non_empty(value: Option<&String>) -> Option<String>
+{
+    value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty())
+}
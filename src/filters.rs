@@ -0,0 +1,143 @@
+// Picks episodes by their date, duration, title and media type, for 'list' and 'get'. Every filter that's set has to
+// match, and an episode that's missing what a filter looks at, like a date, doesn't match it.
+
+use std::str::FromStr;
+use std::time::Duration;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeZone};
+use regex::{Regex, RegexBuilder};
+use rss::Item;
+use crate::error::{Error, Result};
+use crate::feed_fields::{episode_enclosure, episode_title};
+use crate::utils::{parse_duration, parse_pub_date};
+
+// File extensions for enclosures whose MIME type doesn't say what they are, like 'application/octet-stream'.
+const AUDIO_EXTENSIONS: [&str; 7] = ["mp3", "m4a", "aac", "ogg", "oga", "opus", "wav"];
+const VIDEO_EXTENSIONS: [&str; 5] = ["mp4", "m4v", "mov", "webm", "mkv"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MediaType
+{
+    Audio,
+    Video
+}
+
+impl FromStr for MediaType
+{
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self>
+    {
+        match name
+        {
+            "audio" => Ok(MediaType::Audio),
+            "video" => Ok(MediaType::Video),
+            _ => Err(Error::Invalid(format!("Unknown media type '{}'. It should be 'audio' or 'video'.", name)))
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EpisodeFilter
+{
+    pub since: Option<DateTime<FixedOffset>>,
+    pub before: Option<DateTime<FixedOffset>>,
+    pub min_duration: Option<Duration>,
+    pub max_duration: Option<Duration>,
+    pub title_regex: Option<Regex>,
+    pub media_type: Option<MediaType>,
+    pub has_enclosure: bool
+}
+
+impl EpisodeFilter
+{
+    pub fn is_empty(&self) -> bool
+    {
+        self.since.is_none() && self.before.is_none() && self.min_duration.is_none() && self.max_duration.is_none()
+            && self.title_regex.is_none() && self.media_type.is_none() && !self.has_enclosure
+    }
+
+    pub fn matches(&self, episode: &Item) -> bool
+    {
+        let date = episode.pub_date.as_deref().and_then(parse_pub_date);
+        let duration = episode.itunes_ext.as_ref().and_then(|itunes| itunes.duration.as_deref()).and_then(parse_duration);
+
+        self.since.is_none_or(|since| date.is_some_and(|date| date >= since))
+            && self.before.is_none_or(|before| date.is_some_and(|date| date < before))
+            && self.min_duration.is_none_or(|min_duration| duration.is_some_and(|duration| duration >= min_duration))
+            && self.max_duration.is_none_or(|max_duration| duration.is_some_and(|duration| duration <= max_duration))
+            && self.title_regex.as_ref().is_none_or(|title_regex| episode_title(episode).is_some_and(|title| title_regex.is_match(&title)))
+            && self.media_type.is_none_or(|media_type| episode_media_type(episode) == Some(media_type))
+            && (!self.has_enclosure || episode_enclosure(episode).is_some())
+    }
+
+    // The indices of the matching episodes.
+    pub fn apply(&self, episodes: &[Item]) -> Vec<usize>
+    {
+        episodes.iter().enumerate().filter(|(_, episode)| self.matches(episode)).map(|(episode_index, _)| episode_index).collect()
+    }
+}
+
+pub fn episode_media_type(episode: &Item) -> Option<MediaType>
+{
+    let enclosure = episode_enclosure(episode)?;
+
+    if enclosure.mime_type.starts_with("audio/") { return Some(MediaType::Audio) }
+    if enclosure.mime_type.starts_with("video/") { return Some(MediaType::Video) }
+
+    let path = enclosure.url.split(['?', '#']).next().unwrap_or_default();
+    let extension = path.rsplit_once('.').map(|(_, extension)| extension.to_lowercase())?;
+    if AUDIO_EXTENSIONS.contains(&extension.as_str()) { Some(MediaType::Audio) }
+    else if VIDEO_EXTENSIONS.contains(&extension.as_str()) { Some(MediaType::Video) }
+    else { None }
+}
+
+// Takes a day like '2026-01-01', which starts at local midnight, or an exact RFC 3339 time.
+pub fn parse_date_bound(raw_date: &str) -> Result<DateTime<FixedOffset>>
+{
+    let raw_date = raw_date.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(raw_date) { return Ok(date) }
+
+    NaiveDate::parse_from_str(raw_date, "%Y-%m-%d").ok()
+        .and_then(|date| Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest())
+        .map(DateTime::<FixedOffset>::from)
+        .ok_or_else(|| Error::Invalid(format!("Invalid date '{}'. Use a day like '2026-01-01'.", raw_date)))
+}
+
+// Takes amounts with units, like '20m', '1h30m', '90s' or '1d', or a duration like '1:30:00'. A plain number is minutes.
+pub fn parse_duration_bound(raw_duration: &str) -> Result<Duration>
+{
+    let raw_duration = raw_duration.trim();
+    let invalid = || Error::Invalid(format!("Invalid duration '{}'. Use something like '20m', '1h30m' or '45s'.", raw_duration));
+
+    if raw_duration.contains(':') { return parse_duration(raw_duration).ok_or_else(invalid) }
+    if let Ok(minutes) = raw_duration.parse::<u64>() { return minutes.checked_mul(60).map(Duration::from_secs).ok_or_else(invalid) }
+
+    let mut total_seconds: u64 = 0;
+    let mut amount = String::new();
+    for character in raw_duration.chars()
+    {
+        if character.is_ascii_digit() { amount.push(character); continue }
+
+        let unit_seconds = match character.to_ascii_lowercase()
+        {
+            'd' => 86400,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid())
+        };
+        let seconds = amount.parse::<u64>().ok().and_then(|amount| amount.checked_mul(unit_seconds)).ok_or_else(invalid)?;
+        total_seconds = total_seconds.checked_add(seconds).ok_or_else(invalid)?;
+        amount.clear();
+    }
+
+    if !amount.is_empty() || raw_duration.is_empty() { return Err(invalid()) }
+    Ok(Duration::from_secs(total_seconds))
+}
+
+// Titles are matched case-insensitively, since feeds aren't consistent about capitalization.
+pub fn parse_title_regex(raw_pattern: &str) -> Result<Regex>
+{
+    RegexBuilder::new(raw_pattern).case_insensitive(true).build()
+        .map_err(|error| Error::Invalid(format!("Invalid title pattern '{}': {}", raw_pattern, error)))
+}
@@ -0,0 +1,305 @@
+// Full-text search over the episodes in podcasts' cached feeds. Queries are words and "quoted phrases", which all have
+// to match unless joined by OR. NOT or a leading '-' excludes, and parentheses group, like 'climate (policy OR law) -ads'.
+
+use chrono::{DateTime, FixedOffset};
+use rss::Item;
+use serde::Serialize;
+use crate::error::{Error, Result};
+use crate::feed_fields::*;
+use crate::manage_feeds::{load_cached_feed, ordered_episodes, EpisodeOrder};
+use crate::structs::Podcast;
+use crate::utils::parse_pub_date;
+
+// How much more a match in the title counts than one in the description or show notes.
+const TITLE_WEIGHT: usize = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query
+{
+    Word(String),
+    Phrase(Vec<String>),
+    All(Vec<Query>),
+    Any(Vec<Query>),
+    Not(Box<Query>)
+}
+
+// An episode that matched, with its index as 'list' and 'get' count them, and the id 'get -' takes.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FoundEpisode
+{
+    pub alias: String,
+    pub index: usize,
+    pub id: String,
+    pub title: Option<String>,
+    pub published: Option<String>,
+    pub score: usize,
+
+    #[serde(skip)]
+    pub date: Option<DateTime<FixedOffset>>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token
+{
+    Word(String),
+    Phrase(Vec<String>),
+    Open,
+    Close,
+    And,
+    Or,
+    Not
+}
+
+// An episode's searchable text, split into lowercase words.
+struct Document
+{
+    title: Vec<String>,
+    body: Vec<String>
+}
+
+pub fn parse_query(raw_query: &str) -> Result<Query>
+{
+    let tokens = tokenize_query(raw_query)?;
+    let mut position = 0;
+    let query = parse_any(&tokens, &mut position)?;
+
+    match tokens.get(position)
+    {
+        None => Ok(query),
+        Some(Token::Close) => Err(Error::Invalid(String::from("The query has a ')' without a '('."))),
+        Some(_) => Err(Error::Invalid(String::from("The query couldn't be understood.")))
+    }
+}
+
+// Searches the given podcasts' cached feeds, best matches first and newer episodes first among equal ones. Podcasts
+// without a usable cache are skipped.
+pub fn find_episodes(query: &Query, podcasts: &[Podcast], selected: &[usize]) -> Vec<FoundEpisode>
+{
+    let mut found: Vec<FoundEpisode> = Vec::new();
+
+    for podcast in selected.iter().map(|index| &podcasts[*index])
+    {
+        let Ok(feed) = load_cached_feed(podcast) else { continue };
+
+        for (episode_index, episode) in ordered_episodes(&feed, EpisodeOrder::default()).iter().enumerate()
+        {
+            let document = Document::from(episode);
+            if !document.matches(query) { continue }
+
+            let date = episode.pub_date.as_deref().and_then(parse_pub_date);
+            found.push(FoundEpisode
+            {
+                alias: podcast.alias.clone(),
+                index: episode_index,
+                id: episode_id(episode),
+                title: episode_title(episode),
+                published: date.map(|date| date.to_rfc3339()),
+                score: document.score(query),
+                date
+            });
+        }
+    }
+
+    found.sort_by(|first, second| second.score.cmp(&first.score).then(second.date.cmp(&first.date)));
+    found
+}
+
+
+fn tokenize_query(raw_query: &str) -> Result<Vec<Token>>
+{
+    let mut tokens = Vec::new();
+    let mut characters = raw_query.chars().peekable();
+
+    while let Some(character) = characters.next()
+    {
+        match character
+        {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '-' => tokens.push(Token::Not),
+            '"' =>
+            {
+                let phrase: String = characters.by_ref().take_while(|character| *character != '"').collect();
+                let words = split_words(&phrase);
+                if !words.is_empty() { tokens.push(Token::Phrase(words)) }
+            }
+            character if character.is_whitespace() => {}
+            character =>
+            {
+                let mut word = String::from(character);
+                while let Some(next) = characters.peek().filter(|next| !next.is_whitespace() && !"()\"".contains(**next))
+                {
+                    word.push(*next);
+                    characters.next();
+                }
+
+                match word.as_str()
+                {
+                    "AND" => tokens.push(Token::And),
+                    "OR" => tokens.push(Token::Or),
+                    "NOT" => tokens.push(Token::Not),
+                    _ =>
+                    {
+                        // Words like "don't" or "covid-19" are searched as the words they'd be split into anyway.
+                        let words = split_words(&word);
+                        match words.len()
+                        {
+                            0 => {}
+                            1 => tokens.push(Token::Word(words[0].clone())),
+                            _ => tokens.push(Token::Phrase(words))
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if tokens.is_empty() { return Err(Error::Invalid(String::from("The query is empty."))) }
+    Ok(tokens)
+}
+
+fn parse_any(tokens: &[Token], position: &mut usize) -> Result<Query>
+{
+    let mut alternatives = vec![parse_all(tokens, position)?];
+    while tokens.get(*position) == Some(&Token::Or)
+    {
+        *position += 1;
+        alternatives.push(parse_all(tokens, position)?);
+    }
+
+    Ok(if alternatives.len() == 1 { alternatives.remove(0) } else { Query::Any(alternatives) })
+}
+
+fn parse_all(tokens: &[Token], position: &mut usize) -> Result<Query>
+{
+    let mut parts = vec![parse_unary(tokens, position)?];
+    loop
+    {
+        match tokens.get(*position)
+        {
+            None | Some(Token::Or) | Some(Token::Close) => break,
+            Some(Token::And) => *position += 1,
+            Some(_) => {}
+        }
+        parts.push(parse_unary(tokens, position)?);
+    }
+
+    Ok(if parts.len() == 1 { parts.remove(0) } else { Query::All(parts) })
+}
+
+fn parse_unary(tokens: &[Token], position: &mut usize) -> Result<Query>
+{
+    let token = tokens.get(*position).cloned()
+        .ok_or_else(|| Error::Invalid(String::from("The query ends with an operator that's missing what comes after it.")))?;
+    *position += 1;
+
+    match token
+    {
+        Token::Word(word) => Ok(Query::Word(word)),
+        Token::Phrase(words) => Ok(Query::Phrase(words)),
+        Token::Not => Ok(Query::Not(Box::new(parse_unary(tokens, position)?))),
+        Token::Open =>
+        {
+            let query = parse_any(tokens, position)?;
+            if tokens.get(*position) != Some(&Token::Close) { return Err(Error::Invalid(String::from("The query has a '(' without a ')'."))) }
+            *position += 1;
+            Ok(query)
+        }
+        Token::Close | Token::And | Token::Or => Err(Error::Invalid(String::from("The query has an operator where a word should be.")))
+    }
+}
+
+impl From<&Item> for Document
+{
+    fn from(episode: &Item) -> Self
+    {
+        let title = episode_title(episode).unwrap_or_default();
+
+        // The description and the show notes are often the same text, so the notes are only added when they differ.
+        let mut body = strip_html(&episode_description(episode).unwrap_or_default());
+        if let Some(notes) = &episode.content
+        {
+            let notes = strip_html(notes);
+            if notes != body { body = format!("{} {}", body, notes) }
+        }
+
+        Document { title: split_words(&title), body: split_words(&body) }
+    }
+}
+
+impl Document
+{
+    fn matches(&self, query: &Query) -> bool
+    {
+        match query
+        {
+            Query::Word(_) | Query::Phrase(_) => self.occurrences(query) > 0,
+            Query::All(parts) => parts.iter().all(|part| self.matches(part)),
+            Query::Any(parts) => parts.iter().any(|part| self.matches(part)),
+            Query::Not(part) => !self.matches(part)
+        }
+    }
+
+    // Counts the matches of every word and phrase that isn't excluded. Phrases count double, since they say more
+    // about what was meant.
+    fn score(&self, query: &Query) -> usize
+    {
+        match query
+        {
+            Query::Word(_) => self.occurrences(query),
+            Query::Phrase(_) => self.occurrences(query) * 2,
+            Query::All(parts) | Query::Any(parts) => parts.iter().map(|part| self.score(part)).sum(),
+            Query::Not(_) => 0
+        }
+    }
+
+    fn occurrences(&self, query: &Query) -> usize
+    {
+        let words: &[String] = match query
+        {
+            Query::Word(word) => std::slice::from_ref(word),
+            Query::Phrase(words) => words,
+            _ => return 0
+        };
+
+        count_sequence(&self.title, words) * TITLE_WEIGHT + count_sequence(&self.body, words)
+    }
+}
+
+fn count_sequence(text: &[String], words: &[String]) -> usize
+{
+    if words.is_empty() || text.len() < words.len() { return 0 }
+    text.windows(words.len()).filter(|window| *window == words).count()
+}
+
+fn split_words(text: &str) -> Vec<String>
+{
+    text.split(|character: char| !character.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+// Show notes are usually HTML. Only the text is searched, so a link or tag name doesn't count as a match.
+fn strip_html(html: &str) -> String
+{
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+
+    for character in html.chars()
+    {
+        match character
+        {
+            '<' => in_tag = true,
+            '>' if in_tag =>
+            {
+                in_tag = false;
+                text.push(' ');
+            }
+            character if !in_tag => text.push(character),
+            _ => {}
+        }
+    }
+
+    text.replace("&amp;", "&").replace("&nbsp;", " ").replace("&quot;", "\"").replace("&#39;", "'").replace("&lt;", "<").replace("&gt;", ">")
+}
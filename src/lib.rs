@@ -0,0 +1,25 @@
+// Podclaw's sources use Allman-style braces, which leave a blank line before most 'else' blocks.
+#![allow(clippy::suspicious_else_formatting)]
+
+pub mod error;
+pub use crate::error::{Error, Result};
+
+pub mod structs;
+pub mod utils;
+pub mod manage_feeds;
+pub mod podcast_namespace;
+pub mod feed_fields;
+pub mod discovery;
+pub mod search;
+pub mod http;
+pub mod auth;
+pub mod secrets;
+pub mod config;
+pub mod theme;
+pub mod records;
+pub mod find;
+pub mod filters;
+pub mod scheduler;
+pub mod schedule;
+pub mod auto_download;
+pub mod testing;
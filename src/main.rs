@@ -1,26 +1,44 @@
+// Podclaw's sources use Allman-style braces, which leave a blank line before most 'else' blocks.
+#![allow(clippy::suspicious_else_formatting)]
+
+use std::fs::{create_dir_all, remove_file, write};
+use std::io::{stderr, stdin, stdout, BufRead, IsTerminal, Write};
 use std::path::PathBuf;
-use clap::{Parser, Subcommand};
+use std::process::{Command, ExitCode, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, FixedOffset};
+use clap::{Args as ClapArgs, Parser, Subcommand};
+use podclaw::{Error, Result};
+use regex::Regex;
+use podclaw::structs::Podcast;
+use podclaw::utils::*;
+use podclaw::manage_feeds::*;
+use podclaw::podcast_namespace::*;
+use podclaw::feed_fields::episode_id;
+use podclaw::http::{HttpClient, NetworkOverrides, ReqwestClient};
+use podclaw::auth::{parse_header, AuthenticatedClient, FeedAuth, FeedCredentials, Secret, SecretHeader};
+use podclaw::secrets::*;
+use podclaw::discovery::FeedCandidate;
+use podclaw::search::search_podcasts;
+use podclaw::config::{load_config, Config};
+use podclaw::theme::ColorMode;
+use podclaw::records::*;
+use podclaw::find::{find_episodes, parse_query};
+use podclaw::filters::*;
+use podclaw::schedule::*;
+use podclaw::auto_download::{run_auto_download, AutoDownloadEdit};
 
 #[macro_use]
-mod utils;
-use crate::utils::*;
-
-mod structs;
-use crate::structs::*;
-
-mod manage_feeds;
-use crate::manage_feeds::*;
-
-pub const TXTD: TextDeco =
-    TextDeco {
-        completion: "\x1b[1m\x1b[38;2;51;153;51m[✓]\x1b[0m\x1b[0m", // green
-        general: "\x1b[1m\x1b[38;2;109;186;219m[*]\x1b[0m\x1b[0m", // light blue
-        important: "\x1b[1m\x1b[38;2;255;140;0m[#]\x1b[0m\x1b[0m", // orange
-        error: "\x1b[1m\x1b[38;2;255;50;50m[!]\x1b[0m\x1b[0m", // red
-        prompt: "\x1b[1m\x1b[38;2;89;40;138m[?]\x1b[0m\x1b[0m", // darker purple
-        input: "\x1b[1m\x1b[38;2;166;77;255m[>]\x1b[0m\x1b[0m", // bright purple
-        verbose: "\x1b[1m\x1b[38;2;72;135;195m[+]\x1b[0m\x1b[0m" // darker blue
-    };
+mod display;
+use crate::display::*;
+
+mod table;
+use crate::table::*;
+
+mod daemon;
+use crate::daemon::run_daemon;
 
 #[derive(Parser)]
 #[command(long_about = None)]
@@ -30,79 +48,210 @@ pub const TXTD: TextDeco =
 struct Args
 {
     #[command(subcommand)]
-    command: Option<Commands>
+    command: Option<Commands>,
+
+    #[command(flatten)]
+    settings: SettingsArgs,
+
+    /// Prints structured records instead of text: 'json' prints one document, 'ndjson' one line per record as soon as
+    /// it's ready. Other messages go to stderr.
+    #[arg(required = false, long = "output", global = true, default_value = "text", value_name = "text|json|ndjson")]
+    output: OutputFormat
+}
+
+// Overrides for the config file's settings, which in turn are overridden by the 'PODCLAW_*' environment variables.
+#[derive(ClapArgs, Clone)]
+struct SettingsArgs
+{
+    /// Where podcasts without a download path get their own folder, named after their alias.
+    #[arg(required = false, long = "download-root", global = true)]
+    download_root: Option<PathBuf>,
+
+    /// How episode files are named. Can use '{alias}', '{index}', '{title}' and '{date}'.
+    #[arg(required = false, long = "filename-template", global = true)]
+    filename_template: Option<String>,
+
+    /// How many podcasts 'update' and 'get' work on at once.
+    #[arg(required = false, long = "concurrency", global = true)]
+    concurrency: Option<usize>,
+
+    /// The output theme: 'default', 'basic', 'plain', or one from the config.
+    #[arg(required = false, long = "theme", global = true)]
+    theme: Option<String>,
+
+    /// When to use colors: 'auto' only does when writing to a terminal and 'NO_COLOR' isn't set.
+    #[arg(required = false, long = "color", global = true, value_name = "auto|always|never")]
+    color: Option<ColorMode>
+}
+
+impl SettingsArgs
+{
+    fn apply_to(self, config: &mut Config)
+    {
+        if self.download_root.is_some() { config.defaults.download_root = self.download_root }
+        if self.filename_template.is_some() { config.defaults.filename_template = self.filename_template }
+        if self.concurrency.is_some() { config.defaults.concurrency = self.concurrency }
+        if self.theme.is_some() { config.output.theme = self.theme }
+        if self.color.is_some() { config.output.color = self.color }
+    }
 }
 
+// Only one command is ever parsed, so the size of its biggest variant doesn't matter.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands
 {
     /// Registers a podcast with Podclaw.
+    #[command(override_usage = "podclaw add [OPTIONS] [ALIAS] <LINK> [<DOWNLOAD_PATH> <INTERVAL>]")]
     Add
     {
-        /// Case-insensitive. A nickname that's used to point to a podcast.
-        alias: String,
-
-        /// The URL that hosts a podcast's RSS feed.
-        link: String,
-
-        /// The path where Podclaw will put downloaded episodes.
-        download_path: PathBuf,
+        /// In order: an optional, case-insensitive alias that's used to point to the podcast, the URL of its RSS feed or a web
+        /// page that links to it, the path where Podclaw will put downloaded episodes, and the interval in hours before its
+        /// cache is deemed outdated. Without an alias, one is made up from the podcast's title. The path and interval can be
+        /// left out together when the podcast's tags set defaults for them.
+        #[arg(required = true, num_args = 1..=4, value_names = ["ALIAS", "LINK", "DOWNLOAD_PATH", "INTERVAL"])]
+        arguments: Vec<String>,
 
-        /// Interpreted as hours. The interval before a cache is deemed outdated. Outdated caches are typically automatically updated.
-        interval: usize,
+        /// Tags the podcast, like 'news'. Can be given more than once.
+        #[arg(required = false, short = 't', long = "tag")]
+        tags: Vec<String>,
 
         /// Marks a podcast as locked. This means it won't automatically update its cache, and it can't be edited.
         #[arg(required = false, short = 'l', long = "lock")]
-        should_lock: bool
+        should_lock: bool,
+
+        #[command(flatten)]
+        network: NetworkArgs,
+
+        #[command(flatten)]
+        credentials: CredentialArgs
+    },
+
+    /// Searches a podcast directory for feeds. A result can be added right away with '--add'.
+    Search
+    {
+        /// What to search for, like a show's name or host.
+        #[arg(required = true)]
+        terms: Vec<String>,
+
+        /// How many results to show at most.
+        #[arg(required = false, short = 'n', long = "limit")]
+        limit: Option<usize>,
+
+        /// Registers one of the results, with this alias if one is given. Needs '--path' and '--interval' too, unless its tags
+        /// set defaults for them.
+        #[arg(required = false, long = "add", value_name = "ALIAS", num_args = 0..=1, default_missing_value = "")]
+        add_alias: Option<String>,
+
+        /// The number of the result to add. It's asked for when there are several and this isn't given.
+        #[arg(required = false, long = "pick", requires = "add_alias")]
+        pick: Option<usize>,
+
+        /// The path where Podclaw will put downloaded episodes of the added podcast.
+        #[arg(required = false, short = 'p', long = "path", requires = "add_alias")]
+        download_path: Option<PathBuf>,
+
+        /// Interpreted as hours. The interval before the added podcast's cache is deemed outdated.
+        #[arg(required = false, short = 'i', long = "interval", requires = "add_alias")]
+        interval: Option<usize>,
+
+        /// Tags the added podcast. Can be given more than once.
+        #[arg(required = false, short = 't', long = "tag", requires = "add_alias")]
+        tags: Vec<String>
+    },
+
+    /// Searches the titles, descriptions and show notes of every cached episode, best matches first.
+    Find
+    {
+        /// Words that all have to appear, and "quoted phrases". 'OR' goes between alternatives, 'NOT' or a '-' goes before
+        /// what mustn't appear, and parentheses group. Quote the whole query when it uses these, like 'climate (policy OR
+        /// law) -ads'.
+        #[arg(required = true)]
+        query: Vec<String>,
+
+        /// Only searches podcasts with this tag. Can be given more than once.
+        #[arg(required = false, short = 't', long = "tag")]
+        tags: Vec<String>,
+
+        /// Shows only this many of the best matches.
+        #[arg(required = false, short = 'n', long = "limit")]
+        limit: Option<usize>,
+
+        /// Prints only each match's alias and id, separated by a tab, to pipe into 'podclaw get -'.
+        #[arg(required = false, long = "ids")]
+        ids_only: bool
     },
 
-    /// Removes a registered podcast.
+    /// Removes registered podcasts.
     Remove
     {
-        /// Case-insensitive. Alias of the podcast to remove.
-        alias: String
+        #[command(flatten)]
+        selection: PodcastSelection
     },
 
     /// Inspects a podcast or episode, displaying their details.
     Inspect
     {
-        /// Case-insensitive. Alias of the podcast to inspect.
+        /// Case-insensitive. Alias of the podcast to inspect. Its start is enough, if no other alias starts the same way.
         alias: String,
 
         /// Optional. An index to point to a specific episode. If provided, that episode will be inspected instead of the podcast itself.
         #[arg(required = false)]
         episode_index: Option<usize>,
 
-        /// Flips the episode indices around. Technically, Podclaw does this by default, but this reverts it.
-        #[arg(required = false, short = 'r', long = "reverse")]
-        reversal_flag: bool
+        #[command(flatten)]
+        order: OrderArgs,
+
+        /// Shows the podcast's passwords, tokens, and header values instead of hiding them.
+        #[arg(required = false, long = "show-secrets")]
+        show_secrets: bool
     },
 
-    /// Downloads an episode of a podcast.
+    /// Downloads an episode of one or more podcasts.
+    #[command(override_usage = "podclaw get [OPTIONS] <ALIASES>... <EPISODE_INDEX>\n       podclaw get [OPTIONS] --tag <TAG> <EPISODE_INDEX>\n       podclaw get [OPTIONS] --all <EPISODE_INDEX>\n       podclaw get [OPTIONS] <FILTERS>... [ALIASES]...\n       podclaw find --ids <QUERY>... | podclaw get [OPTIONS] -")]
     Get
     {
-        /// Case-insensitive. Alias of the podcast to get an episode from.
-        alias: String,
+        /// Case-insensitive aliases of the podcasts, or glob patterns like 'news-*', followed by the index of the episode to
+        /// download from each. The start of an alias is enough, if no other alias starts the same way. A lone '-' reads
+        /// the episodes from 'find --ids' instead. With filters like '--since', there's no episode index, and every
+        /// matching episode is downloaded.
+        #[arg(required = false, num_args = 1.., value_names = ["ALIASES", "EPISODE_INDEX"])]
+        arguments: Vec<String>,
+
+        /// Downloads the episode from every podcast with this tag. Can be given more than once.
+        #[arg(required = false, short = 't', long = "tag")]
+        tags: Vec<String>,
+
+        /// Downloads the episode from every registered podcast.
+        #[arg(required = false, long = "all", conflicts_with = "tags")]
+        all: bool,
+
+        #[command(flatten)]
+        order: OrderArgs,
 
-        /// Index of the episode to download.
-        episode_index: usize,
+        /// Also downloads the episode's Podcasting 2.0 chapters file, as JSON, next to the audio.
+        #[arg(required = false, long = "with-chapters")]
+        with_chapters: bool,
 
-        /// Does nothing if an alias isn't provided. Flips the episode indices around. Technically, Podclaw does this by default, but this reverts it.
-        #[arg(required = false, short = 'r', long = "reverse")]
-        reversal_flag: bool
+        /// Also downloads the episode's SRT or VTT transcripts next to the audio.
+        #[arg(required = false, long = "with-transcript")]
+        with_transcript: bool,
+
+        #[command(flatten)]
+        filters: FilterArgs
     },
 
-    /// Edits a registered podcast. All arguments are optional.
+    /// Edits registered podcasts. All arguments are optional.
     Edit
     {
-        /// Case-insensitive. Alias of the podcast to edit.
-        alias: String,
+        #[command(flatten)]
+        selection: PodcastSelection,
 
-        /// Case-insensitive. A nickname that's used to point to a podcast.
+        /// Case-insensitive. A nickname that's used to point to a podcast. Only works on a single podcast.
         #[arg(required = false, short = 'a', long = "alias")]
         new_alias: Option<String>,
 
-        /// The URL that hosts a podcast's RSS feed.
+        /// The URL that hosts a podcast's RSS feed. Only works on a single podcast.
         #[arg(required = false, short = 'l', long = "link")]
         new_link: Option<String>,
 
@@ -113,13 +262,34 @@ enum Commands
         /// Interpreted as hours. The interval before a cache is deemed outdated. Outdated caches are typically automatically updated.
         #[arg(required = false, short = 'i', long = "interval")]
         new_interval: Option<usize>,
+
+        /// Clears this podcast's network overrides, so it goes back to using the config file's settings.
+        #[arg(required = false, long = "reset-network")]
+        reset_network: bool,
+
+        /// Removes this podcast's username and password or bearer token.
+        #[arg(required = false, long = "clear-auth", conflicts_with_all = ["username", "bearer"])]
+        clear_auth: bool,
+
+        /// Removes all of this podcast's custom headers, before adding any given with '--header'.
+        #[arg(required = false, long = "reset-headers")]
+        reset_headers: bool,
+
+        #[command(flatten)]
+        network: NetworkArgs,
+
+        #[command(flatten)]
+        credentials: CredentialArgs,
+
+        #[command(flatten)]
+        auto_download: AutoDownloadArgs
     },
 
-    /// Updates the cache of a podcast.
+    /// Updates the cache of podcasts, and downloads new episodes for podcasts with auto-download rules.
     Update
     {
-        /// Case-insensitive. Alias of the podcast to update.
-        alias: String,
+        #[command(flatten)]
+        selection: PodcastSelection
     },
 
     /// Repairs the storage file. Since this will delete any registered podcasts, it must be ran with a confirmation flag to work.
@@ -130,11 +300,26 @@ enum Commands
         confirmation_flag: bool
     },
 
-    /// Toggles the lock of a registered podcast.
+    /// Toggles the lock of registered podcasts.
     Lock
     {
-        /// Case-insensitive. Alias of the podcast to lock/unlock.
-        alias: String,
+        #[command(flatten)]
+        selection: PodcastSelection
+    },
+
+    /// Manages the encrypted secrets file, which keeps passwords and tokens for private feeds. It's unlocked with a
+    /// passphrase, which is asked for or read from the 'PODCLAW_PASSPHRASE' environment variable.
+    Secret
+    {
+        #[command(subcommand)]
+        action: SecretAction
+    },
+
+    /// Adds tags to podcasts, removes them, or lists them.
+    Tag
+    {
+        #[command(subcommand)]
+        action: TagAction
     },
 
     /// Lists all registered podcasts, or all episodes in a specific one.
@@ -144,119 +329,1540 @@ enum Commands
         #[arg(required = false)]
         alias: Option<String>,
 
-        /// Does nothing if an alias isn't provided. Flips the episode indices around. Technically, Podclaw does this by default, but this reverts it.
-        #[arg(required = false, short = 'r', long = "reverse")]
-        reversal_flag: bool
+        /// Only lists podcasts with this tag. Can be given more than once.
+        #[arg(required = false, short = 't', long = "tag", conflicts_with = "alias")]
+        tags: Vec<String>,
+
+        /// Groups the podcasts by their tags.
+        #[arg(required = false, short = 'g', long = "group", conflicts_with = "alias")]
+        group: bool,
+
+        #[command(flatten)]
+        order: OrderArgs,
+
+        #[command(flatten)]
+        table: TableOptions,
+
+        #[command(flatten)]
+        filters: FilterArgs
+    },
+
+    /// Marks episodes as played, which 'list' shows. Marking them again with '--unplayed' undoes it.
+    Played
+    {
+        /// Case-insensitive. Alias of the podcast. Its start is enough, if no other alias starts the same way.
+        alias: String,
+
+        /// The indices of the episodes, as 'list' shows them.
+        #[arg(required = true, num_args = 1..)]
+        episode_indices: Vec<usize>,
+
+        /// Marks the episodes as unplayed instead.
+        #[arg(required = false, long = "unplayed")]
+        unplayed: bool,
+
+        #[command(flatten)]
+        order: OrderArgs
+    },
+
+    /// Installs, shows or removes a systemd user timer or crontab line that runs 'update --all' every so often, for
+    /// scheduled syncing without a long-running process.
+    Schedule
+    {
+        #[command(subcommand)]
+        action: ScheduleAction
+    },
+
+    /// Keeps running in the foreground and refreshes each podcast's cache when its update interval is up. Changes to the
+    /// storage and config are picked up on their own. Stops on SIGTERM or Ctrl+C.
+    Daemon
+    {
+        /// Appends the log to this file instead of writing it to stderr.
+        #[arg(required = false, long = "log-file")]
+        log_file: Option<PathBuf>
     }
 }
 
-fn main()
+// The podcasts a command works on, given by alias, by glob pattern, by tag, or all at once.
+#[derive(ClapArgs)]
+struct PodcastSelection
 {
-    let args = Args::parse();
+    /// Case-insensitive. Aliases of the podcasts, or glob patterns like 'news-*'. The start of an alias is enough, if no other alias starts the same way.
+    #[arg(required_unless_present_any = ["all", "tags"], value_name = "ALIASES")]
+    aliases: Vec<String>,
 
-    match args.command
+    /// Selects every podcast with this tag. Can be given more than once.
+    #[arg(required = false, short = 't', long = "tag")]
+    tags: Vec<String>,
+
+    /// Selects every registered podcast.
+    #[arg(required = false, long = "all", conflicts_with_all = ["aliases", "tags"])]
+    all: bool
+}
+
+impl PodcastSelection
+{
+    fn resolve(&self, podcasts: &[Podcast]) -> Result<Vec<usize>>
     {
-        Some(Commands::Add {alias, link, download_path, interval, should_lock}) =>
+        if self.all
         {
-            if alias == ""
-            {
-                println!("{} Invalid alias.", TXTD.error);
-                return;
-            }
+            if podcasts.is_empty() { return Err(Error::NotFound(String::from("No podcasts are registered."))) }
+            return Ok((0..podcasts.len()).collect());
+        }
 
-            let podcasts = get_storage();
-            add_podcast(alias.to_lowercase(), link, download_path, interval, should_lock, podcasts);
+        let mut selected = select_podcasts(&self.aliases, podcasts)?;
+        if !self.tags.is_empty()
+        {
+            for index in podcasts_tagged(&self.tags, podcasts)? { if !selected.contains(&index) { selected.push(index) } }
         }
 
-        Some(Commands::Remove {alias}) =>
-            {
-                let podcasts = get_storage();
+        Ok(selected)
+    }
 
-                if let Some(index) = find_podcast(&alias.to_lowercase(), &podcasts)
-                {
-                    remove_podcast(index, podcasts);
-                }
+    // The podcasts named by their alias, rather than picked out by '--all', a tag or a glob pattern.
+    fn named(&self, podcasts: &[Podcast]) -> Result<Vec<usize>>
+    {
+        let aliases: Vec<String> = self.aliases.iter().filter(|alias| !is_glob(alias)).cloned().collect();
+        select_podcasts(&aliases, podcasts)
+    }
+}
 
-                else { println!("{} There is no podcast with that alias.", TXTD.error) }
-            }
+// Per-podcast network overrides. Anything not given falls back to the '[network]' section of the config file.
+#[derive(ClapArgs)]
+struct NetworkArgs
+{
+    /// Interpreted as seconds. How long to wait for a connection to the server.
+    #[arg(required = false, long = "connect-timeout")]
+    connect_timeout: Option<u64>,
 
-        Some(Commands::Inspect {alias, episode_index, reversal_flag}) =>
+    /// Interpreted as seconds. How long the server may go quiet, while answering or in the middle of a download.
+    #[arg(required = false, long = "read-timeout")]
+    read_timeout: Option<u64>,
+
+    /// How many times to retry after connection errors, timeouts, and 5xx responses.
+    #[arg(required = false, long = "retries")]
+    retries: Option<u32>,
+
+    /// Interpreted as milliseconds. The wait before the first retry, which doubles after each one.
+    #[arg(required = false, long = "retry-backoff")]
+    retry_backoff: Option<u64>,
+
+    /// The User-Agent header sent with every request.
+    #[arg(required = false, long = "user-agent")]
+    user_agent: Option<String>,
+
+    /// An HTTP, HTTPS, or SOCKS5 proxy URL, like 'socks5://127.0.0.1:9050'. An empty string disables the config's proxy.
+    #[arg(required = false, long = "proxy")]
+    proxy: Option<String>
+}
+
+impl NetworkArgs
+{
+    fn into_overrides(self) -> NetworkOverrides
+    {
+        NetworkOverrides
         {
-            let mut podcasts = get_storage();
+            connect_timeout: self.connect_timeout,
+            read_timeout: self.read_timeout,
+            retries: self.retries,
+            retry_backoff: self.retry_backoff,
+            user_agent: self.user_agent,
+            proxy: self.proxy
+        }
+    }
+}
 
-            if let Some(podcast_index) = find_podcast(&alias.to_lowercase(), &podcasts)
-            {
+// How episode indices are counted, for every command that takes them. '-r' is what picked the feed's own order before
+// '--order' existed, and it's kept so older scripts still work.
+#[derive(ClapArgs)]
+struct OrderArgs
+{
+    /// How episodes are counted: 'oldest' first, 'newest' first, or in the order the 'feed' lists them. Episodes are
+    /// sorted by their publish date, and ones without a date come last.
+    #[arg(required = false, long = "order", default_value = "oldest", value_name = "ORDER")]
+    order: EpisodeOrder,
 
-                podcasts = do_autocache(podcast_index, podcasts);
+    #[arg(required = false, short = 'r', long = "reverse", hide = true, conflicts_with = "order")]
+    reversal_flag: bool
+}
 
-                if episode_index != None
-                {
-                    println!("Episode index provided: {}", episode_index.unwrap());
-                    inspect_podcast(podcast_index, episode_index.unwrap(), true, reversal_flag, podcasts);
-                }
+impl OrderArgs
+{
+    fn resolve(&self) -> EpisodeOrder
+    {
+        if self.reversal_flag { EpisodeOrder::Feed } else { self.order }
+    }
+}
 
-                else { inspect_podcast(podcast_index, 0, false, reversal_flag, podcasts) }
-            }
+// Picks episodes for 'list' and 'get'. All the given filters have to match.
+#[derive(ClapArgs)]
+struct FilterArgs
+{
+    /// Only episodes published on or after this day, like '2026-01-01', or an exact RFC 3339 time.
+    #[arg(required = false, long = "since", value_parser = parse_date_bound, value_name = "DATE")]
+    since: Option<DateTime<FixedOffset>>,
+
+    /// Only episodes published before this day, like '2026-02-01', or an exact RFC 3339 time.
+    #[arg(required = false, long = "before", value_parser = parse_date_bound, value_name = "DATE")]
+    before: Option<DateTime<FixedOffset>>,
+
+    /// Only episodes at least this long, like '20m' or '1h30m'. A plain number is minutes.
+    #[arg(required = false, long = "min-duration", value_parser = parse_duration_bound, value_name = "DURATION")]
+    min_duration: Option<Duration>,
+
+    /// Only episodes at most this long, like '45m'. A plain number is minutes.
+    #[arg(required = false, long = "max-duration", value_parser = parse_duration_bound, value_name = "DURATION")]
+    max_duration: Option<Duration>,
+
+    /// Only episodes whose titles match this case-insensitive regular expression, like '^interview'.
+    #[arg(required = false, long = "title-regex", value_parser = parse_title_regex, value_name = "PATTERN")]
+    title_regex: Option<Regex>,
+
+    /// Only 'audio' or 'video' episodes, going by the type of their enclosure.
+    #[arg(required = false, long = "type", value_name = "TYPE")]
+    media_type: Option<MediaType>,
+
+    /// Only episodes with something to download.
+    #[arg(required = false, long = "has-enclosure")]
+    has_enclosure: bool
+}
+
+impl FilterArgs
+{
+    fn into_filter(self) -> EpisodeFilter
+    {
+        EpisodeFilter
+        {
+            since: self.since,
+            before: self.before,
+            min_duration: self.min_duration,
+            max_duration: self.max_duration,
+            title_regex: self.title_regex,
+            media_type: self.media_type,
+            has_enclosure: self.has_enclosure
+        }
+    }
+}
+
+// A podcast's auto-download rules, for 'edit'. They're followed by 'update' and 'daemon' right after a refresh.
+#[derive(ClapArgs)]
+struct AutoDownloadArgs
+{
+    /// Downloads new episodes whenever this podcast is updated. Episodes published before now don't count as new, unless
+    /// '--auto-since' says otherwise.
+    #[arg(required = false, long = "auto-download", conflicts_with = "no_auto_download")]
+    auto_download: bool,
+
+    /// Stops downloading new episodes on updates. The rules are kept for turning it back on.
+    #[arg(required = false, long = "no-auto-download")]
+    no_auto_download: bool,
+
+    /// Keeps only this many of the newest matching episodes, deleting older ones that auto-download fetched. 0 keeps them all.
+    #[arg(required = false, long = "keep-latest", value_name = "COUNT")]
+    keep_latest: Option<usize>,
 
-            else { println!("{} There is no podcast with that alias.", TXTD.error) }
+    /// Only downloads episodes whose titles match this case-insensitive regular expression. An empty pattern removes it.
+    #[arg(required = false, long = "include-title", value_name = "PATTERN")]
+    include_title: Option<String>,
+
+    /// Skips episodes whose titles match this case-insensitive regular expression. An empty pattern removes it.
+    #[arg(required = false, long = "exclude-title", value_name = "PATTERN")]
+    exclude_title: Option<String>,
+
+    /// Only downloads episodes at least this long, like '20m'. A plain number is minutes.
+    #[arg(required = false, long = "auto-min-duration", value_parser = parse_duration_bound, value_name = "DURATION")]
+    min_duration: Option<Duration>,
+
+    /// Only downloads episodes at most this long, like '2h'. A plain number is minutes.
+    #[arg(required = false, long = "auto-max-duration", value_parser = parse_duration_bound, value_name = "DURATION")]
+    max_duration: Option<Duration>,
+
+    /// Counts episodes published on or after this day as new, like '2026-01-01', or an exact RFC 3339 time.
+    #[arg(required = false, long = "auto-since", value_parser = parse_date_bound, value_name = "DATE")]
+    since: Option<DateTime<FixedOffset>>,
+
+    /// Clears every auto-download rule and turns it off, before applying any other auto-download options.
+    #[arg(required = false, long = "reset-auto-download")]
+    reset: bool
+}
+
+impl AutoDownloadArgs
+{
+    fn into_edit(self) -> AutoDownloadEdit
+    {
+        AutoDownloadEdit
+        {
+            enabled: if self.auto_download { Some(true) } else if self.no_auto_download { Some(false) } else { None },
+            keep_latest: self.keep_latest,
+            include_pattern: self.include_title,
+            exclude_pattern: self.exclude_title,
+            min_duration: self.min_duration,
+            max_duration: self.max_duration,
+            since: self.since.map(SystemTime::from),
+            reset: self.reset
         }
+    }
+}
+
+// Credentials for private feeds. They're sent with feed requests and episode downloads, but never with chapters or transcripts.
+// Each secret can be given as-is, which keeps it unencrypted in the storage, or as the name of an entry in the secrets file.
+#[derive(ClapArgs)]
+struct CredentialArgs
+{
+    /// The username for HTTP Basic authentication. Needs '--password' or '--password-secret' too.
+    #[arg(required = false, long = "username", requires = "basic_password", conflicts_with = "bearer")]
+    username: Option<String>,
+
+    /// The password for HTTP Basic authentication. Needs '--username' too.
+    #[arg(required = false, long = "password", group = "basic_password", requires = "username")]
+    password: Option<String>,
+
+    /// The name of a stored secret to use as the HTTP Basic password. Needs '--username' too.
+    #[arg(required = false, long = "password-secret", group = "basic_password", requires = "username")]
+    password_secret: Option<String>,
+
+    /// A token sent as 'Authorization: Bearer <token>'.
+    #[arg(required = false, long = "bearer-token", group = "bearer")]
+    bearer_token: Option<String>,
+
+    /// The name of a stored secret to send as a bearer token.
+    #[arg(required = false, long = "bearer-token-secret", group = "bearer")]
+    bearer_token_secret: Option<String>,
+
+    /// An extra header sent with every request, like 'X-Api-Key: abc123'. Can be given more than once.
+    #[arg(required = false, long = "header")]
+    headers: Vec<String>,
+
+    /// An extra header whose value comes from a stored secret, like 'X-Api-Key: supercast-key'. Can be given more than once.
+    #[arg(required = false, long = "header-secret")]
+    secret_headers: Vec<String>
+}
+
+impl CredentialArgs
+{
+    fn has_plain_secrets(&self) -> bool
+    {
+        self.password.is_some() || self.bearer_token.is_some() || !self.headers.is_empty()
+    }
 
-        Some(Commands::Get { alias, episode_index, reversal_flag }) =>
+    // Splits these arguments into the authentication to set, if any, and the extra headers.
+    fn into_parts(self) -> Result<(Option<FeedAuth>, Vec<SecretHeader>)>
+    {
+        let mut headers = Vec::new();
+        for header in &self.headers
         {
-            let mut podcasts = get_storage();
+            let (name, value) = parse_header(header)?;
+            headers.push((name, Secret::Plain(value)));
+        }
 
-            if let Some(podcast_index) = find_podcast(&alias.to_lowercase(), &podcasts)
-            {
-                podcasts = do_autocache(podcast_index, podcasts);
-                get_episode(podcast_index, episode_index, reversal_flag, podcasts);
-            }
+        for header in &self.secret_headers
+        {
+            let (name, secret_name) = parse_header(header)?;
+            headers.push((name, Secret::Stored(secret_name)));
         }
 
-        Some(Commands::Edit { alias, new_alias, new_link, new_download_path, new_interval }) =>
+        let password = self.password.map(Secret::Plain).or(self.password_secret.map(Secret::Stored));
+        let token = self.bearer_token.map(Secret::Plain).or(self.bearer_token_secret.map(Secret::Stored));
+
+        let auth = match (self.username, password, token)
         {
-            let podcasts = get_storage();
+            (Some(username), Some(password), _) => Some(FeedAuth::Basic { username, password }),
+            (_, _, Some(token)) => Some(FeedAuth::Bearer { token }),
+            _ => None
+        };
 
-            if let Some(index) = find_podcast(&alias.to_lowercase(), &podcasts)
-            {
-                edit_podcast(index, new_alias, new_link, new_download_path,new_interval, podcasts);
-            }
+        Ok((auth, headers))
+    }
+}
+
+#[derive(Subcommand)]
+enum TagAction
+{
+    /// Adds tags to a podcast.
+    Add
+    {
+        /// Case-insensitive. Alias of the podcast to tag. Its start is enough, if no other alias starts the same way.
+        alias: String,
+
+        /// The tags to add, like 'news' or 'tech'.
+        #[arg(required = true)]
+        tags: Vec<String>
+    },
+
+    /// Removes tags from a podcast.
+    Remove
+    {
+        /// Case-insensitive. Alias of the podcast to untag. Its start is enough, if no other alias starts the same way.
+        alias: String,
+
+        /// The tags to remove.
+        #[arg(required = true)]
+        tags: Vec<String>
+    },
+
+    /// Lists every tag in use, and how many podcasts have it.
+    List
+}
+
+#[derive(Subcommand)]
+enum SecretAction
+{
+    /// Stores a secret, replacing any existing one with the same name. The value is asked for if it isn't given.
+    Set
+    {
+        /// The name podcasts use to refer to this secret.
+        name: String,
 
-            else { println!("{} There is no podcast with that alias.", TXTD.error) }
+        /// The secret itself. Leaving this out keeps it out of your shell history.
+        #[arg(required = false, long = "value")]
+        value: Option<String>
+    },
+
+    /// Deletes a stored secret.
+    Remove
+    {
+        /// The name of the secret to delete.
+        name: String
+    },
+
+    /// Lists the names of all stored secrets, without their values.
+    List
+}
+
+#[derive(Subcommand)]
+enum ScheduleAction
+{
+    /// Writes a systemd user service and timer into '~/.config/systemd/user' and enables the timer, or adds a crontab
+    /// line with '--cron'. Installing again replaces the old schedule.
+    Install
+    {
+        /// How often to update, like '30m', '6h' or '1d'. A plain number is minutes.
+        #[arg(required = false, long = "every", default_value = "1h", value_parser = parse_duration_bound, value_name = "DURATION")]
+        every: Duration,
+
+        /// Adds a line to the user's crontab instead of writing systemd units.
+        #[arg(required = false, long = "cron")]
+        cron: bool,
+
+        /// Only writes the systemd units, without enabling the timer through 'systemctl --user'.
+        #[arg(required = false, long = "no-enable", conflicts_with = "cron")]
+        no_enable: bool
+    },
+
+    /// Shows whether the systemd timer or the crontab line is installed, and how often it runs.
+    Status,
+
+    /// Disables and deletes the systemd units, and removes the crontab line, whichever are installed.
+    Remove
+}
+
+fn main() -> ExitCode
+{
+    let args = Args::parse();
+    let result = run(args);
+
+    // Results gathered before a failure are still printed, so scripts can see which podcasts went through.
+    finish_records();
+
+    match result
+    {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) =>
+        {
+            eprintln!("{} {}", TXTD.error, error);
+            ExitCode::from(exit_code(&error))
+        }
+    }
+}
+
+// Each kind of failure gets its own exit code, so scripts can tell them apart. Clap already uses 2 for bad arguments.
+fn exit_code(error: &Error) -> u8
+{
+    match error
+    {
+        Error::Invalid(_) => 1,
+        Error::Network(_) => 3,
+        Error::Parse(_) => 4,
+        Error::Storage(_) => 5,
+        Error::NotFound(_) => 6,
+        Error::Locked(_) => 7
+    }
+}
+
+fn run(args: Args) -> Result<()>
+{
+    use_output_format(args.output);
+    let mut config = load_config()?;
+    args.settings.clone().apply_to(&mut config);
+    use_theme(&config)?;
+
+    match args.command
+    {
+        Some(Commands::Add {arguments, tags, should_lock, network, credentials}) =>
+        {
+            let (alias, link, download_path, interval) = split_add_arguments(arguments)?;
+            let (download_path, interval) = with_defaults(&config, &tags, download_path, interval)?;
+            if credentials.has_plain_secrets() { warn_about_plain_secrets() }
+            let (auth, headers) = credentials.into_parts()?;
+            let credentials = FeedCredentials { auth: auth.unwrap_or_default(), headers };
+
+            let new = NewPodcast
+            {
+                alias, feed_url: link, download_path, download_root: config.download_root(), interval, is_locked: should_lock,
+                network: network.into_overrides(), credentials, tags, choose_feed: Some(choose_feed)
+            };
+            register_podcast(&config, new)?;
         }
 
-        Some(Commands::Update {alias}) =>
+        Some(Commands::Search { terms, limit, add_alias, pick, download_path, interval, tags }) =>
+        {
+            let mut settings = config.search.clone();
+            if limit.is_some() { settings.limit = limit }
+
+            let api_secret = match &settings.api_secret_name
             {
-                let podcasts = get_storage();
+                Some(secret_name) => Some(Secret::Stored(secret_name.clone()).resolve(&unlock_secrets()?)?),
+                None => None
+            };
 
-                if let Some(index) = find_podcast(&alias.to_lowercase(), &podcasts)
+            let terms = terms.join(" ");
+            report!("{} Searching {} for {}...", TXTD.general, settings.base_url(), italicize!(format!("\'{}\'", terms)));
+            let client = client_for(&config, &NetworkOverrides::default())?;
+            let results = search_podcasts(&client, &settings, api_secret.as_deref(), &terms)?;
+            if is_structured_output() && add_alias.is_none() { print_records(&results) }
+            print_search_results(&results);
+
+            if let Some(alias) = add_alias
+            {
+                let (download_path, interval) = with_defaults(&config, &tags, download_path, interval)?;
+                let result_index = match pick
                 {
-                    update_podcast(index, podcasts);
-                }
+                    Some(result_index) => result_index,
+                    None if results.len() == 1 => 0,
+                    None if results.is_empty() => return Err(Error::NotFound(String::from("There's nothing to add."))),
+                    None => prompt_for_index(results.len())
+                        .ok_or_else(|| Error::Invalid(String::from("Several podcasts were found. Choose one with '--pick'.")))?
+                };
+
+                let result = results.get(result_index)
+                    .ok_or_else(|| Error::NotFound(format!("There's no result #{}.", result_index)))?;
 
-                else { println!("{} There is no podcast with that alias.", TXTD.error) }
+                let new = NewPodcast
+                {
+                    alias, feed_url: result.feed_url.clone(), download_path, download_root: config.download_root(), interval, tags,
+                    choose_feed: Some(choose_feed), ..Default::default()
+                };
+                register_podcast(&config, new)?;
             }
+        }
 
-        Some(Commands::Repair {confirmation_flag}) =>
-        { repair_storage(confirmation_flag) }
+        Some(Commands::Find { query, tags, limit, ids_only }) =>
+        {
+            let query = parse_query(&query.join(" "))?;
+            let podcasts = get_storage()?;
+            let searched: Vec<usize> = if tags.is_empty() { (0..podcasts.len()).collect() } else { podcasts_tagged(&tags, &podcasts)? };
 
-        Some(Commands::Lock {alias}) =>
+            let mut found = find_episodes(&query, &podcasts, &searched);
+            if let Some(limit) = limit { found.truncate(limit) }
+
+            if is_structured_output() { print_records(&found) }
+            else if ids_only { for episode in &found { println!("{}\t{}", episode.alias, episode.id) } }
+            else if found.is_empty() { report!("{} No episodes found.", TXTD.important) }
+            else { page(&render_found_episodes(&found)?, &TableOptions::default()) }
+        }
+
+        Some(Commands::Remove {selection}) =>
         {
-            let podcasts = get_storage();
+            let mut podcasts = get_storage()?;
+            let mut selected = selection.resolve(&podcasts)?;
 
-            if let Some(index) = find_podcast(&alias.to_lowercase(), &podcasts)
+            // Removing from the back keeps the indices of the podcasts still to be removed valid.
+            selected.sort_unstable_by(|first, second| second.cmp(first));
+            run_batch(&mut podcasts, &selected, "remove", |podcasts, index|
             {
-                lock_podcast(index, podcasts);
-            }
+                report!("{} Removing {} from podcasts...", TXTD.general, italicize!(format!("\'{}\'", podcasts[index].alias)));
+                remove_podcast(index, podcasts);
+                Ok(Outcome::new(true))
+            })?;
 
-            else { println!("{} There is no podcast with that alias.", TXTD.error) }
+            report!("{} Done!", TXTD.completion);
         }
 
-        Some(Commands::List { alias, reversal_flag }) =>
+        Some(Commands::Inspect {alias, episode_index, order, show_secrets}) =>
         {
-            let podcasts = get_storage();
-            list_podcasts_or_episodes(alias, reversal_flag, podcasts);
-        }
+            let mut podcasts = get_storage()?;
+            let podcast_index = require_podcast(&alias, &podcasts)?;
 
-        None => { println!("{} No commands provided. Run \'help\' to see all options.", TXTD.error) }
-    }
-}
\ No newline at end of file
+            // Only an outdated cache needs the network, so a fresh one doesn't ask for the secrets passphrase.
+            if is_cache_outdated(&podcasts[podcast_index])
+            {
+                let base_client = client_for(&config, &podcasts[podcast_index].network)?;
+                let client = authenticated(&base_client, &podcasts[podcast_index].credentials, &LazySecrets::default())?;
+                if autocache(&client, &mut podcasts[podcast_index]) { save_storage(&podcasts)? }
+            }
+            let feed = load_cached_feed(&podcasts[podcast_index])?;
+
+            if let Some(episode_index) = episode_index
+            {
+                let episodes = ordered_episodes(&feed, order.resolve());
+                let episode = select_episode(&episodes, episode_index)?;
+                if is_structured_output() { print_record(&episode_record(&feed, episode_index, episode)) }
+                else
+                {
+                    report!("Episode index provided: {}", episode_index);
+                    print_episode(&feed, episode_index, episode);
+                }
+            }
+
+            else if is_structured_output()
+            {
+                print_record(&podcast_record(podcast_index, &podcasts[podcast_index], show_secrets));
+            }
+
+            else
+            {
+                print_series(&feed);
+                print_tags(&podcasts[podcast_index]);
+                print_auto_download(&podcasts[podcast_index]);
+                print_credentials(&podcasts[podcast_index].credentials, show_secrets);
+                print_feed_history(&podcasts[podcast_index]);
+            }
+        }
+
+        Some(Commands::Get { arguments, tags, all, order, with_chapters, with_transcript, filters }) =>
+        {
+            let order = order.resolve();
+            let (selection, targets) = split_get_arguments(arguments, tags, all, filters.into_filter())?;
+            let mut podcasts = get_storage()?;
+            let selected = selection.resolve(&podcasts)?;
+            let secrets = LazySecrets::default();
+            secrets.unlock_for(&podcasts, &selected)?;
+
+            run_parallel_batch(&mut podcasts, &selected, config.concurrency(), "download", |podcast|
+            {
+                let base_client = client_for(&config, &podcast.network)?;
+                let client = authenticated(&base_client, &podcast.credentials, &secrets)?;
+                let changed = autocache(&client, podcast);
+
+                let mut files = Vec::new();
+                for episode_index in targets.indices_for(podcast, order)?
+                {
+                    files.extend(get_episode(&client, podcast, episode_index, order, with_chapters, with_transcript, config.filename_template())?);
+                }
+                Ok(Outcome { files, ..Outcome::new(changed) })
+            })?;
+        }
+
+        Some(Commands::Edit { selection, new_alias, new_link, new_download_path, new_interval, reset_network, clear_auth, reset_headers, network, credentials, auto_download }) =>
+        {
+            let mut podcasts = get_storage()?;
+            let selected = selection.resolve(&podcasts)?;
+
+            if selected.len() > 1 && (new_alias.is_some() || new_link.is_some())
+            {
+                return Err(Error::Invalid(String::from("Several podcasts can't share an alias or feed link. Edit them one at a time!")));
+            }
+
+            let network = network.into_overrides();
+            let changes_network = reset_network || !network.is_empty();
+            if credentials.has_plain_secrets() { warn_about_plain_secrets() }
+            let (auth, headers) = credentials.into_parts()?;
+            let auth = if clear_auth { Some(FeedAuth::None) } else { auth };
+            let changes_credentials = auth.is_some() || reset_headers || !headers.is_empty();
+            let auto_download = auto_download.into_edit();
+            let changes_auto_download = !auto_download.is_empty();
+            let edit = PodcastEdit
+            {
+                alias: new_alias.clone(), feed_url: new_link.clone(), download_path: new_download_path.clone(), interval: new_interval,
+                network, reset_network, auth, headers, reset_headers, auto_download
+            };
+
+            run_batch(&mut podcasts, &selected, "edit", |podcasts, index|
+            {
+                report!("{} Editing {}...", TXTD.general, italicize!(format!("\'{}\'", podcasts[index].alias)));
+                if !edit_podcast(index, podcasts, edit.clone())?
+                {
+                    report!("{} No changes made.", TXTD.important);
+                    return Ok(Outcome::new(false));
+                }
+
+                if new_alias.is_some() { report!("{} Changed alias to {}!", TXTD.general, italicize!(format!("\'{}\'", podcasts[index].alias))) }
+                if let Some(new_link) = &new_link { report!("{} Changed feed link to {}!", TXTD.general, italicize!(format!("\'{}\'", new_link))) }
+                if let Some(new_dl_path) = &new_download_path { report!("{} Changed download path to {}!", TXTD.general, italicize!(format!("\'{}\'", new_dl_path.display()))) }
+                if let Some(new_interval) = new_interval { report!("{} Changed update interval to {}!", TXTD.general, italicize!(format!("\'{}\'", new_interval))) }
+                if changes_network { report!("{} Changed network settings!", TXTD.general) }
+                if changes_credentials { report!("{} Changed credentials!", TXTD.general) }
+                if changes_auto_download
+                {
+                    match podcasts[index].auto_download.describe()
+                    {
+                        Some(rules) => report!("{} Auto-download is on: {}.", TXTD.general, rules),
+                        None => report!("{} Auto-download is off.", TXTD.general)
+                    }
+                }
+
+                report!("{} Successfully edited podcast!", TXTD.completion);
+                Ok(Outcome::new(true))
+            })?;
+        }
+
+        Some(Commands::Update {selection}) =>
+        {
+            let mut podcasts = get_storage()?;
+            let selected = selection.resolve(&podcasts)?;
+            let named: Vec<String> = selection.named(&podcasts)?.into_iter().map(|index| podcasts[index].alias.clone()).collect();
+            let unlocked: Vec<usize> = selected.iter().copied().filter(|index| !podcasts[*index].is_locked).collect();
+            let secrets = LazySecrets::default();
+            secrets.unlock_for(&podcasts, &unlocked)?;
+
+            run_parallel_batch(&mut podcasts, &selected, config.concurrency(), "update", |podcast|
+            {
+                // A locked podcast that was asked for by name can't be updated, but one that only came along with '--all',
+                // a tag or a glob pattern is kept as it is, which isn't a failure.
+                if podcast.is_locked && named.contains(&podcast.alias)
+                {
+                    return Err(Error::Locked(format!("The podcast '{}' is locked.", podcast.alias)));
+                }
+
+                if podcast.is_locked
+                {
+                    report!("{} Podcast {} is locked, skipping it.", TXTD.important, italicize!(format!("\'{}\'", podcast.alias)));
+                    return Ok(Outcome::skipped());
+                }
+
+                report!("{} Updating podcast {}...", TXTD.general, italicize!(format!("\'{}\'", podcast.alias)));
+                let base_client = client_for(&config, &podcast.network)?;
+                let client = authenticated(&base_client, &podcast.credentials, &secrets)?;
+                let feed_move = update_podcast(&client, podcast)?;
+                if let Some(feed_move) = &feed_move { print_feed_move(feed_move) }
+
+                report!("{} Cache updated!", TXTD.completion);
+                let files = auto_download(&client, podcast, config.filename_template())?;
+                Ok(Outcome { feed_move, files, ..Outcome::new(true) })
+            })?;
+        }
+
+        Some(Commands::Repair {confirmation_flag}) =>
+        {
+            if !confirmation_flag { return Err(Error::Invalid(String::from("No confirmation flag was set."))) }
+
+            report!("{} Repairing storage...", TXTD.general);
+            repair_storage()?;
+            emit_record(&ResultRecord::command("repair", true));
+            report!("{} Done!", TXTD.completion);
+        }
+
+        Some(Commands::Lock {selection}) =>
+        {
+            let mut podcasts = get_storage()?;
+            let selected = selection.resolve(&podcasts)?;
+
+            run_batch(&mut podcasts, &selected, "lock", |podcasts, index|
+            {
+                let alias = italicize!(format!("\'{}\'", podcasts[index].alias));
+                if lock_podcast(&mut podcasts[index]) { report!("{} Successfully locked podcast {}!", TXTD.completion, alias) }
+                else { report!("{} Successfully unlocked podcast {}!", TXTD.completion, alias) }
+
+                Ok(Outcome::new(true))
+            })?;
+        }
+
+        Some(Commands::Secret { action }) => manage_secrets(action)?,
+
+        Some(Commands::Tag { action }) => manage_tags(action)?,
+
+        Some(Commands::List { alias, tags, group, order, table, filters }) =>
+        {
+            let podcasts = get_storage()?;
+            let filter = filters.into_filter();
+
+            if let Some(alias) = alias
+            {
+                let index = require_podcast(&alias, &podcasts)?;
+                let feed = load_cached_feed(&podcasts[index])?;
+                let episodes = ordered_episodes(&feed, order.resolve());
+                let shown = filter.apply(&episodes);
+                if is_structured_output()
+                {
+                    let records: Vec<EpisodeRecord> = shown.iter().map(|index| episode_record(&feed, *index, &episodes[*index])).collect();
+                    print_records(&records);
+                }
+
+                else { page(&render_episode_list(&podcasts[index], &episodes, &file_indices(&feed, order.resolve()), &shown, config.filename_template(), &table)?, &table) }
+            }
+
+            else if !filter.is_empty()
+            {
+                return Err(Error::Invalid(String::from("Episode filters need the alias of the podcast whose episodes to list.")));
+            }
+
+            else
+            {
+                let listed: Vec<usize> = if tags.is_empty() { (0..podcasts.len()).collect() } else { podcasts_tagged(&tags, &podcasts)? };
+                if is_structured_output()
+                {
+                    let records: Vec<PodcastRecord> = listed.iter().map(|index| podcast_record(*index, &podcasts[*index], false)).collect();
+                    print_records(&records);
+                }
+
+                else if group { page(&render_podcast_groups(&podcasts, &listed, &table)?, &table) }
+                else { page(&render_podcast_list(&podcasts, &listed, &table)?, &table) }
+            }
+        }
+
+        Some(Commands::Played { alias, episode_indices, unplayed, order }) =>
+        {
+            let mut podcasts = get_storage()?;
+            let index = require_podcast(&alias, &podcasts)?;
+            let feed = load_cached_feed(&podcasts[index])?;
+            let episodes = ordered_episodes(&feed, order.resolve());
+            let marked = episode_indices.iter()
+                .map(|episode_index| select_episode(&episodes, *episode_index))
+                .collect::<Result<Vec<_>>>()?;
+
+            let changed = mark_played(&mut podcasts[index], &marked, !unplayed);
+            if changed > 0 { save_storage(&podcasts)? }
+
+            let state = if unplayed { "unplayed" } else { "played" };
+            emit_record(&ResultRecord::succeeded(&podcasts[index].alias, state, changed > 0));
+            match changed
+            {
+                0 => report!("{} These episodes were already marked as {}.", TXTD.important, state),
+                1 => report!("{} Marked 1 episode as {}!", TXTD.completion, state),
+                _ => report!("{} Marked {} episodes as {}!", TXTD.completion, changed, state)
+            }
+        }
+
+        Some(Commands::Schedule { action }) => manage_schedule(action)?,
+
+        Some(Commands::Daemon { log_file }) =>
+        {
+            let settings = args.settings;
+            run_daemon(config, ||
+            {
+                let mut config = load_config()?;
+                settings.clone().apply_to(&mut config);
+                Ok(config)
+            }, log_file)?;
+        }
+
+        None => { return Err(Error::Invalid(String::from("No commands provided. Run \'help\' to see all options."))) }
+    }
+
+    Ok(())
+}
+
+// The alias is optional but comes first. The download path and interval are optional too, but only together, so the
+// number of arguments tells which ones were given: one is the link, two add the alias, three add the path and interval
+// instead, and four are everything.
+fn split_add_arguments(mut arguments: Vec<String>) -> Result<(String, String, Option<PathBuf>, Option<usize>)>
+{
+    let (download_path, interval) = if arguments.len() >= 3
+    {
+        let raw_interval = arguments.pop().unwrap_or_default();
+        let interval = raw_interval.parse()
+            .map_err(|_| Error::Invalid(format!("Invalid interval '{}'. It should be a whole number of hours.", raw_interval)))?;
+        (arguments.pop().map(PathBuf::from), Some(interval))
+    }
+    else { (None, None) };
+
+    let link = arguments.pop().unwrap_or_default();
+    let alias = arguments.pop().unwrap_or_default();
+
+    Ok((alias, link, download_path, interval))
+}
+
+// Fills in a new podcast's download path and interval when they weren't given, from its tags' defaults first and the
+// config's after that. An empty path means a folder in the download root.
+fn with_defaults(config: &Config, tags: &[String], download_path: Option<PathBuf>, interval: Option<usize>) -> Result<(PathBuf, usize)>
+{
+    let tag_defaults = config.tag_defaults(tags);
+    let interval = interval.or(tag_defaults.interval).unwrap_or(config.interval());
+
+    match download_path.or(tag_defaults.download_path)
+    {
+        Some(download_path) => Ok((download_path, interval)),
+        None if config.download_root().is_some() => Ok((PathBuf::new(), interval)),
+        None => Err(Error::Invalid(String::from("No download path was given, and neither the podcast's tags nor 'download_root' in the config set one.")))
+    }
+}
+
+// Which episodes 'get' downloads: the one at the same index in every podcast, the ones 'find --ids' listed, by alias
+// and episode id, or every downloadable one that matches the filters.
+enum EpisodeTargets
+{
+    Index(usize),
+    Ids(Vec<(String, String)>),
+    Matching(EpisodeFilter)
+}
+
+impl EpisodeTargets
+{
+    fn indices_for(&self, podcast: &Podcast, order: EpisodeOrder) -> Result<Vec<usize>>
+    {
+        if let EpisodeTargets::Index(episode_index) = self { return Ok(vec![*episode_index]) }
+
+        let feed = load_cached_feed(podcast)?;
+        let episodes = ordered_episodes(&feed, order);
+        let ids = match self
+        {
+            EpisodeTargets::Ids(ids) => ids,
+            EpisodeTargets::Matching(filter) => return Ok(filter.apply(&episodes)),
+            EpisodeTargets::Index(_) => unreachable!()
+        };
+
+        ids.iter().filter(|(alias, _)| *alias == podcast.alias).map(|(_, id)|
+        {
+            episodes.iter().position(|episode| episode_id(episode) == *id)
+                .ok_or_else(|| Error::NotFound(format!("There's no episode with the id '{}' anymore.", id)))
+        }).collect()
+    }
+}
+
+// The episode index comes last, after the aliases, unless filters pick the episodes. Tags can stand in for the aliases,
+// and '--all' replaces them.
+fn split_get_arguments(mut arguments: Vec<String>, tags: Vec<String>, all: bool, mut filter: EpisodeFilter) -> Result<(PodcastSelection, EpisodeTargets)>
+{
+    if !filter.is_empty()
+    {
+        let is_valid = if all { arguments.is_empty() } else { !arguments.is_empty() || !tags.is_empty() };
+        if !is_valid { return Err(Error::Invalid(String::from("Give either the aliases or tags of the podcasts, or '--all', along with the filters."))) }
+
+        // Episodes without audio can't be downloaded, so they're left out instead of failing the whole podcast.
+        filter.has_enclosure = true;
+        return Ok((PodcastSelection { aliases: arguments, tags, all }, EpisodeTargets::Matching(filter)));
+    }
+
+    if arguments == ["-"] && tags.is_empty() && !all
+    {
+        let ids = read_episode_ids(stdin().lock())?;
+        let mut aliases: Vec<String> = Vec::new();
+        for (alias, _) in &ids { if !aliases.contains(alias) { aliases.push(alias.clone()) } }
+
+        if aliases.is_empty() { return Err(Error::Invalid(String::from("No episodes were given to download."))) }
+        return Ok((PodcastSelection { aliases, tags, all }, EpisodeTargets::Ids(ids)));
+    }
+
+    let raw_index = arguments.pop().unwrap_or_default();
+    let episode_index = raw_index.parse()
+        .map_err(|_| Error::Invalid(format!("Invalid episode index '{}'. It should be a whole number.", raw_index)))?;
+
+    let is_valid = if all { arguments.is_empty() } else { !arguments.is_empty() || !tags.is_empty() };
+    if !is_valid
+    {
+        return Err(Error::Invalid(String::from("Give either the aliases or tags of the podcasts, or '--all', followed by an episode index.")));
+    }
+
+    Ok((PodcastSelection { aliases: arguments, tags, all }, EpisodeTargets::Index(episode_index)))
+}
+
+// Reads the lines 'find --ids' prints: an alias and an episode id, separated by a tab.
+fn read_episode_ids(input: impl BufRead) -> Result<Vec<(String, String)>>
+{
+    let mut ids = Vec::new();
+
+    for line in input.lines()
+    {
+        let line = line?;
+        if line.trim().is_empty() { continue }
+
+        let (alias, id) = line.split_once('\t')
+            .ok_or_else(|| Error::Invalid(format!("Expected an alias and an episode id separated by a tab, like 'find --ids' prints, but got '{}'.", line)))?;
+        ids.push((normalize_alias(alias), id.trim_end_matches('\r').to_string()));
+    }
+
+    Ok(ids)
+}
+
+// Adds a new podcast and saves it, showing what it was registered with.
+fn register_podcast(config: &Config, new: NewPodcast) -> Result<()>
+{
+    let mut podcasts = get_storage()?;
+    let base_client = client_for(config, &new.network)?;
+    let client = authenticated(&base_client, &new.credentials, &LazySecrets::default())?;
+    let interval = new.interval;
+
+    report!("{} Registering new podcast...", TXTD.general);
+    let new_podcast = add_podcast(&client, new, &mut podcasts)?;
+
+    report!("{} Podcast will use this alias: {}", TXTD.general, italicize!(format!("\'{}\'", new_podcast.alias)));
+    report!("{} Podcast will use this link: {}", TXTD.general, italicize!(format!("\'{}\'", new_podcast.feed_url)));
+    report!("{} This podcast will save its downloaded files to: {}", TXTD.general, italicize!(format!("\'{}\'", new_podcast.download_path.display())));
+    report!("{} This podcast will keep its cache for this many hours: {}", TXTD.general, italicize!(format!("\'{}\'", interval)));
+    if let Some(credentials) = new_podcast.credentials.describe(false)
+    {
+        report!("{} This podcast will authenticate with: {}", TXTD.general, italicize!(credentials));
+    }
+    report!("{} Parsed RSS feed and created initial cache.", TXTD.general);
+
+    emit_record(&ResultRecord::succeeded(&new_podcast.alias, "add", true));
+
+    report!("{} Writing podcast and cache to storage...", TXTD.general);
+    save_storage(&podcasts)?;
+
+    report!("{} Done!", TXTD.completion);
+    Ok(())
+}
+
+// What a batch action did to one podcast. Besides saving the storage, it makes up the podcast's result record.
+#[derive(Default)]
+struct Outcome
+{
+    changed: bool,
+    skipped: bool,
+    feed_move: Option<FeedMove>,
+    files: Vec<PathBuf>
+}
+
+impl Outcome
+{
+    fn new(changed: bool) -> Self
+    {
+        Outcome { changed, ..Default::default() }
+    }
+
+    fn skipped() -> Self
+    {
+        Outcome { skipped: true, ..Default::default() }
+    }
+}
+
+// Runs an action on each selected podcast. A failure is reported and the rest still run, and the storage is written
+// once at the end if any action changed it. Each podcast's result is also emitted as a record.
+fn run_batch(podcasts: &mut Vec<Podcast>, selected: &[usize], action_name: &str, mut action: impl FnMut(&mut Vec<Podcast>, usize) -> Result<Outcome>) -> Result<()>
+{
+    let mut changed = false;
+    let mut failures: Vec<Error> = Vec::new();
+
+    for index in selected.iter().copied()
+    {
+        let alias = podcasts[index].alias.clone();
+        let outcome = action(podcasts, index);
+        emit_record(&result_record(&alias, action_name, &outcome));
+
+        match outcome
+        {
+            Ok(outcome) => changed |= outcome.changed,
+
+            // A lone podcast's failure is reported like any other error, once everything is saved.
+            Err(error) if selected.len() == 1 => failures.push(error),
+            Err(error) =>
+            {
+                print_batch_failure(&alias, &error);
+                failures.push(error);
+            }
+        }
+    }
+
+    finish_batch(podcasts, changed, failures, selected.len())
+}
+
+// Like 'run_batch', but works on up to 'concurrency' podcasts at once. Their output can interleave, so each failure
+// names its podcast.
+fn run_parallel_batch(podcasts: &mut Vec<Podcast>, selected: &[usize], concurrency: usize, action_name: &str, action: impl Fn(&mut Podcast) -> Result<Outcome> + Sync) -> Result<()>
+{
+    if concurrency <= 1 || selected.len() <= 1 { return run_batch(podcasts, selected, action_name, |podcasts, index| action(&mut podcasts[index])) }
+
+    let queue: Vec<&mut Podcast> = podcasts.iter_mut().enumerate()
+        .filter(|(index, _)| selected.contains(index))
+        .map(|(_, podcast)| podcast)
+        .collect();
+    let queue = Mutex::new(queue.into_iter());
+    let outcomes: Mutex<(bool, Vec<Error>)> = Mutex::new((false, Vec::new()));
+
+    thread::scope(|scope|
+    {
+        for _ in 0..concurrency.min(selected.len())
+        {
+            scope.spawn(|| loop
+            {
+                let Some(podcast) = queue.lock().unwrap().next() else { break };
+                let alias = podcast.alias.clone();
+                let outcome = action(podcast);
+                emit_record(&result_record(&alias, action_name, &outcome));
+
+                let mut outcomes = outcomes.lock().unwrap();
+                match outcome
+                {
+                    Ok(outcome) => outcomes.0 |= outcome.changed,
+                    Err(error) =>
+                    {
+                        print_batch_failure(&alias, &error);
+                        outcomes.1.push(error);
+                    }
+                }
+            });
+        }
+    });
+
+    let (changed, failures) = outcomes.into_inner().unwrap();
+    finish_batch(podcasts, changed, failures, selected.len())
+}
+
+fn result_record(alias: &str, action_name: &str, outcome: &Result<Outcome>) -> ResultRecord
+{
+    match outcome
+    {
+        Ok(outcome) if outcome.skipped => ResultRecord::skipped(alias, action_name),
+        Ok(outcome) => ResultRecord
+        {
+            feed_move: outcome.feed_move.as_ref().map(FeedMoveRecord::from),
+            files: outcome.files.clone(),
+            ..ResultRecord::succeeded(alias, action_name, outcome.changed)
+        },
+        Err(error) => ResultRecord::failed(alias, action_name, error)
+    }
+}
+
+fn print_batch_failure(alias: &str, error: &Error)
+{
+    report!("{} {}: {}", TXTD.error, italicize!(format!("\'{}\'", alias)), error);
+}
+
+fn finish_batch(podcasts: &[Podcast], changed: bool, mut failures: Vec<Error>, selected_count: usize) -> Result<()>
+{
+    if changed { save_storage(podcasts)? }
+
+    match failures.len()
+    {
+        0 => Ok(()),
+        1 if selected_count == 1 => Err(failures.remove(0)),
+        failed => Err(failures[0].with_message(format!("{} of {} podcasts failed.", failed, selected_count)))
+    }
+}
+
+// Builds the HTTP client for a podcast, so its network overrides apply to both feed fetches and downloads.
+fn client_for(config: &Config, overrides: &NetworkOverrides) -> Result<ReqwestClient>
+{
+    ReqwestClient::new(config.http_settings(overrides))
+}
+
+// The secrets file, unlocked the first time a podcast needs it, so a batch of podcasts asks for the passphrase only once.
+#[derive(Default)]
+struct LazySecrets
+{
+    store: OnceLock<SecretStore>
+}
+
+impl LazySecrets
+{
+    // Unlocks the file right away if any of these podcasts will need it, so podcasts worked on at once don't all ask.
+    fn unlock_for(&self, podcasts: &[Podcast], selected: &[usize]) -> Result<()>
+    {
+        if selected.iter().any(|index| podcasts[*index].credentials.uses_stored_secrets()) { self.get()?; }
+        Ok(())
+    }
+
+    fn get(&self) -> Result<&SecretStore>
+    {
+        if let Some(store) = self.store.get() { return Ok(store) }
+
+        let store = if get_secrets_path()?.try_exists()? { unlock_secrets()? } else { SecretStore::new() };
+        Ok(self.store.get_or_init(|| store))
+    }
+}
+
+// Adds a podcast's credentials to its client. The secrets file is only unlocked when the credentials actually use it,
+// so public feeds never ask for a passphrase.
+fn authenticated<'a>(client: &'a ReqwestClient, credentials: &FeedCredentials, secrets: &LazySecrets) -> Result<AuthenticatedClient<'a>>
+{
+    if !credentials.uses_stored_secrets() { return AuthenticatedClient::new(client, credentials, &SecretStore::new()) }
+
+    AuthenticatedClient::new(client, credentials, secrets.get()?)
+}
+
+fn unlock_secrets() -> Result<SecretStore>
+{
+    open_secrets_at(&get_secrets_path()?, &read_passphrase(false)?)
+}
+
+fn warn_about_plain_secrets()
+{
+    report!("{} Passwords, tokens and headers given directly are kept unencrypted in the storage. Use the 'secret' command and the '-secret' flags to encrypt them instead.", TXTD.important);
+}
+
+// The passphrase comes from the environment when it's set, and is asked for otherwise. A new secrets file asks twice,
+// since a typo would lock the secrets away for good.
+fn read_passphrase(is_new_file: bool) -> Result<String>
+{
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VARIABLE) { return Ok(passphrase) }
+
+    if !stdin().is_terminal()
+    {
+        return Err(Error::Invalid(format!("The secrets file needs a passphrase. Set '{}' when running Podclaw without a terminal.", PASSPHRASE_VARIABLE)));
+    }
+
+    let passphrase = prompt_hidden(&format!("{} Secrets passphrase: ", TXTD.prompt))?;
+    if is_new_file && prompt_hidden(&format!("{} Repeat the passphrase: ", TXTD.prompt))? != passphrase
+    {
+        return Err(Error::Invalid(String::from("The passphrases don't match.")));
+    }
+
+    Ok(passphrase)
+}
+
+fn prompt_hidden(prompt: &str) -> Result<String>
+{
+    rpassword::prompt_password(prompt).map_err(|error| Error::Invalid(format!("Failed to read from the terminal: {}", error)))
+}
+
+fn manage_tags(action: TagAction) -> Result<()>
+{
+    let mut podcasts = get_storage()?;
+
+    match action
+    {
+        TagAction::Add { alias, tags } =>
+        {
+            let index = require_podcast(&alias, &podcasts)?;
+            let added = tag_podcast(&mut podcasts[index], &tags)?;
+            save_storage(&podcasts)?;
+            emit_record(&ResultRecord::succeeded(&podcasts[index].alias, "tag", !added.is_empty()));
+
+            if added.is_empty() { report!("{} {} already has these tags.", TXTD.important, italicize!(format!("\'{}\'", podcasts[index].alias))) }
+            else { report!("{} Tagged {} with {}!", TXTD.completion, italicize!(format!("\'{}\'", podcasts[index].alias)), added.join(", ")) }
+        }
+
+        TagAction::Remove { alias, tags } =>
+        {
+            let index = require_podcast(&alias, &podcasts)?;
+            let removed = untag_podcast(&mut podcasts[index], &tags)?;
+            save_storage(&podcasts)?;
+            emit_record(&ResultRecord::succeeded(&podcasts[index].alias, "untag", !removed.is_empty()));
+
+            if removed.is_empty() { report!("{} {} has none of these tags.", TXTD.important, italicize!(format!("\'{}\'", podcasts[index].alias))) }
+            else { report!("{} Removed {} from {}!", TXTD.completion, removed.join(", "), italicize!(format!("\'{}\'", podcasts[index].alias))) }
+        }
+
+        TagAction::List if is_structured_output() =>
+        {
+            let records: Vec<TagRecord> = all_tags(&podcasts).into_iter().map(|(tag, podcasts)| TagRecord { tag, podcasts }).collect();
+            print_records(&records);
+        }
+
+        TagAction::List =>
+        {
+            report!("{} Listing all tags...", TXTD.general);
+            for (tag, count) in all_tags(&podcasts)
+            {
+                let podcast_count = if count == 1 { String::from("1 podcast") } else { format!("{} podcasts", count) };
+                report!("  {} {}", italicize!(format!("\'{}\'", tag)), podcast_count);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn manage_secrets(action: SecretAction) -> Result<()>
+{
+    let secrets_path = get_secrets_path()?;
+    let is_new_file = !secrets_path.try_exists()?;
+    let passphrase = read_passphrase(is_new_file && matches!(action, SecretAction::Set { .. }))?;
+    let mut secrets = open_secrets_at(&secrets_path, &passphrase)?;
+
+    match action
+    {
+        SecretAction::Set { name, value } =>
+        {
+            let value = match value
+            {
+                Some(value) => value,
+                None => read_secret_value(&name)?
+            };
+
+            secrets.set(&name, &value)?;
+            save_secrets_at(&secrets, &secrets_path, &passphrase)?;
+            emit_record(&ResultRecord::command("set-secret", true));
+            report!("{} Stored secret {}!", TXTD.completion, italicize!(format!("\'{}\'", name)));
+        }
+
+        SecretAction::Remove { name } =>
+        {
+            if !secrets.remove(&name) { return Err(Error::NotFound(format!("The secret '{}' doesn't exist.", name))) }
+
+            save_secrets_at(&secrets, &secrets_path, &passphrase)?;
+            emit_record(&ResultRecord::command("remove-secret", true));
+            report!("{} Removed secret {}!", TXTD.completion, italicize!(format!("\'{}\'", name)));
+        }
+
+        SecretAction::List if is_structured_output() => print_records(&secrets.names()),
+
+        SecretAction::List =>
+        {
+            report!("{} Listing all stored secrets...", TXTD.general);
+            for name in secrets.names() { report!("  {}", italicize!(format!("\'{}\'", name))) }
+        }
+    }
+
+    Ok(())
+}
+
+fn manage_schedule(action: ScheduleAction) -> Result<()>
+{
+    let unit_dir = systemd_unit_dir()?;
+    let service_path = unit_dir.join(SERVICE_NAME);
+    let timer_path = unit_dir.join(TIMER_NAME);
+
+    match action
+    {
+        ScheduleAction::Install { every, cron, no_enable } =>
+        {
+            // The job has to find the same storage, so a config folder moved with 'PODCLAW_CONFIG_DIR' goes along.
+            let config_dir = match std::env::var_os("PODCLAW_CONFIG_DIR")
+            {
+                Some(_) => Some(std::path::absolute(get_config_dir()?)?),
+                None => None
+            };
+            let spec = ScheduleSpec::new(every, std::env::current_exe()?, config_dir)?;
+
+            if cron
+            {
+                let line = spec.cron_line()?;
+                write_crontab(&with_cron_line(&read_crontab().unwrap_or_default(), &line))?;
+                emit_record(&ResultRecord::command("install-schedule", true));
+                report!("{} Added to the crontab: {}", TXTD.completion, italicize!(line));
+                return Ok(());
+            }
+
+            create_dir_all(&unit_dir)?;
+            write(&service_path, spec.systemd_service())?;
+            write(&timer_path, spec.systemd_timer())?;
+            emit_record(&ResultRecord { files: vec![service_path.clone(), timer_path.clone()], ..ResultRecord::command("install-schedule", true) });
+            report!("{} Wrote {} and {}.", TXTD.general, italicize!(format!("\'{}\'", service_path.display())), italicize!(format!("\'{}\'", timer_path.display())));
+
+            if no_enable { report!("{} Enable it with 'systemctl --user daemon-reload && systemctl --user enable --now {}'.", TXTD.important, TIMER_NAME) }
+            else if run_tool("systemctl", &["--user", "daemon-reload"]) && run_tool("systemctl", &["--user", "enable", "--now", TIMER_NAME])
+            {
+                report!("{} Podcasts will be updated every {}!", TXTD.completion, describe_every(every));
+            }
+
+            else
+            {
+                report!("{} Couldn't enable the timer. Run 'systemctl --user daemon-reload && systemctl --user enable --now {}' yourself.", TXTD.important, TIMER_NAME);
+            }
+        }
+
+        ScheduleAction::Status =>
+        {
+            let systemd_installed = service_path.exists() && timer_path.exists();
+            let status = ScheduleStatus
+            {
+                systemd_installed,
+                systemd_every_seconds: installed_timer_every(&unit_dir).map(|every| every.as_secs()),
+                timer_active: if systemd_installed { timer_state() } else { None },
+                cron_line: read_crontab().as_deref().and_then(find_cron_line).map(str::to_string),
+                unit_dir
+            };
+
+            if is_structured_output() { print_record(&status) }
+            else { print_schedule_status(&status) }
+        }
+
+        ScheduleAction::Remove =>
+        {
+            let mut removed = false;
+            if timer_path.exists() || service_path.exists()
+            {
+                run_tool("systemctl", &["--user", "disable", "--now", TIMER_NAME]);
+                for path in [&timer_path, &service_path].into_iter().filter(|path| path.exists()) { remove_file(path)?; }
+                run_tool("systemctl", &["--user", "daemon-reload"]);
+                report!("{} Removed the systemd timer.", TXTD.completion);
+                removed = true;
+            }
+
+            if let Some(crontab) = read_crontab().filter(|crontab| find_cron_line(crontab).is_some())
+            {
+                write_crontab(&without_cron_line(&crontab))?;
+                report!("{} Removed the crontab line.", TXTD.completion);
+                removed = true;
+            }
+
+            emit_record(&ResultRecord::command("remove-schedule", removed));
+            if !removed { report!("{} No schedule was installed.", TXTD.important) }
+        }
+    }
+
+    Ok(())
+}
+
+// Runs a helper program like 'systemctl', and tells whether it worked. A program that isn't installed just didn't work.
+fn run_tool(program: &str, arguments: &[&str]) -> bool
+{
+    Command::new(program).args(arguments).stdout(Stdio::null()).stderr(Stdio::null())
+        .status().is_ok_and(|status| status.success())
+}
+
+// Whether the timer is running, or nothing when 'systemctl' can't tell, like without a user session.
+fn timer_state() -> Option<bool>
+{
+    let output = Command::new("systemctl").args(["--user", "is-active", TIMER_NAME]).stderr(Stdio::null()).output().ok()?;
+    match String::from_utf8_lossy(&output.stdout).trim()
+    {
+        "active" => Some(true),
+        "inactive" | "failed" => Some(false),
+        _ => None
+    }
+}
+
+// The user's crontab, or nothing when there isn't one yet or cron isn't installed.
+fn read_crontab() -> Option<String>
+{
+    let output = Command::new("crontab").arg("-l").stderr(Stdio::null()).output().ok()?;
+    if !output.status.success() { return None }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn write_crontab(crontab: &str) -> Result<()>
+{
+    let failed = |reason: String| Error::Storage(format!("Couldn't update the crontab: {}", reason));
+
+    let mut child = Command::new("crontab").arg("-").stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::piped())
+        .spawn().map_err(|error| failed(error.to_string()))?;
+    child.stdin.take().map(|mut input| input.write_all(crontab.as_bytes())).transpose()?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() { return Err(failed(String::from_utf8_lossy(&output.stderr).trim().to_string())) }
+    Ok(())
+}
+
+// Asks for a secret without echoing it, or reads it from the first line of input when it's piped in.
+fn read_secret_value(name: &str) -> Result<String>
+{
+    if stdin().is_terminal() { return prompt_hidden(&format!("{} Value for '{}': ", TXTD.prompt, name)) }
+
+    let mut value = String::new();
+    stdin().read_line(&mut value)?;
+    Ok(value.trim_end_matches(['\r', '\n']).to_string())
+}
+
+// Lets the user pick a feed when a web page links to several. Without a terminal, nothing is picked and 'add' lists them.
+fn choose_feed(candidates: &[FeedCandidate]) -> Option<usize>
+{
+    if !stdin().is_terminal() { return None }
+
+    report!("{} This page links to several feeds:", TXTD.prompt);
+    for (candidate_index, candidate) in candidates.iter().enumerate()
+    {
+        report!("  {} {}", make_bold!(format!("#{}:", candidate_index)), italicize!(candidate.description()));
+    }
+
+    prompt_for_index(candidates.len())
+}
+
+// Asks for one of the numbers shown in a list. Returns nothing without a terminal, or when the answer isn't in the list.
+fn prompt_for_index(count: usize) -> Option<usize>
+{
+    if !stdin().is_terminal() { return None }
+
+    if is_structured_output()
+    {
+        eprint!("{} Pick one by its number: ", TXTD.input);
+        stderr().flush().ok()?;
+    }
+
+    else
+    {
+        print!("{} Pick one by its number: ", TXTD.input);
+        stdout().flush().ok()?;
+    }
+
+    let mut choice = String::new();
+    stdin().read_line(&mut choice).ok()?;
+    choice.trim().trim_start_matches('#').parse().ok().filter(|index| *index < count)
+}
+
+// Updates an outdated cache before it's used. Failing to do so isn't fatal, since the old cache still works.
+fn autocache(client: &dyn HttpClient, podcast: &mut Podcast) -> bool
+{
+    if podcast.is_locked || !is_cache_outdated(podcast) { return false }
+
+    report!("{} This podcast's cache is outdated, updating...", TXTD.important);
+    match update_podcast(client, podcast)
+    {
+        Ok(feed_move) =>
+        {
+            if let Some(feed_move) = feed_move { print_feed_move(&feed_move) }
+            report!("{} Cache updated!", TXTD.completion);
+            true
+        }
+
+        Err(error) =>
+        {
+            report!("{} Failed to update cache automatically: {}", TXTD.important, error);
+            false
+        }
+    }
+}
+
+// Follows a freshly updated podcast's auto-download rules, reporting failed episodes without failing the update.
+fn auto_download(client: &AuthenticatedClient, podcast: &mut Podcast, template: &str) -> Result<Vec<PathBuf>>
+{
+    let report = run_auto_download(client, podcast, template)?;
+
+    for path in &report.downloaded { report!("{} Auto-downloaded {}!", TXTD.completion, italicize!(format!("\'{}\'", path.display()))) }
+    for path in &report.removed { report!("{} Deleted {}, which is no longer among the latest episodes.", TXTD.general, italicize!(format!("\'{}\'", path.display()))) }
+    for (path, error) in &report.failures { report!("{} Auto-download failed for {}: {}", TXTD.important, italicize!(format!("\'{}\'", path)), error) }
+
+    Ok(report.downloaded)
+}
+
+// Downloads an episode, and its chapters and transcripts if asked to. Returns the files that were saved.
+fn get_episode(client: &AuthenticatedClient, podcast: &Podcast, episode_index: usize, order: EpisodeOrder, with_chapters: bool, with_transcript: bool, template: &str) -> Result<Vec<PathBuf>>
+{
+    let feed = load_cached_feed(podcast)?;
+    let episodes = ordered_episodes(&feed, order);
+    let target_episode = select_episode(&episodes, episode_index)?;
+    let file_index = file_indices(&feed, order)[episode_index];
+
+    let audio_url = episode_audio_url(target_episode)?;
+    let full_download_path = episode_download_path(podcast, file_index, target_episode, template);
+
+    report!("{} Downloading {}...", TXTD.general, italicize!( format!("\'{}\'", full_download_path.display()) ));
+    download_file(client, &audio_url, &full_download_path)?;
+    report!("{} Done!", TXTD.completion);
+    let mut files = vec![full_download_path];
+
+    let namespace = parse_item_namespace(target_episode, &namespace_prefix(&feed));
+
+    if with_chapters
+    {
+        if let Some((chapters_url, chapters_path)) = chapters_download(podcast, file_index, target_episode, &namespace, template)
+        {
+            report!("{} Downloading chapters to {}...", TXTD.general, italicize!( format!("\'{}\'", chapters_path.display()) ));
+            match download_file(client.without_credentials(), &chapters_url, &chapters_path)
+            {
+                Ok(()) =>
+                {
+                    report!("{} Done!", TXTD.completion);
+                    files.push(chapters_path);
+                }
+                Err(error) => report!("{} {}", TXTD.error, error)
+            }
+        }
+
+        else { report!("{} This episode doesn't publish any chapters.", TXTD.important) }
+    }
+
+    if with_transcript
+    {
+        let transcripts = transcript_downloads(podcast, file_index, target_episode, &namespace, template);
+        if transcripts.is_empty() { report!("{} This episode doesn't publish any SRT or VTT transcripts.", TXTD.important) }
+
+        for (transcript_url, transcript_path) in transcripts
+        {
+            report!("{} Downloading transcript to {}...", TXTD.general, italicize!( format!("\'{}\'", transcript_path.display()) ));
+            match download_file(client.without_credentials(), &transcript_url, &transcript_path)
+            {
+                Ok(()) =>
+                {
+                    report!("{} Done!", TXTD.completion);
+                    files.push(transcript_path);
+                }
+                Err(error) => report!("{} {}", TXTD.error, error)
+            }
+        }
+    }
+
+    Ok(files)
+}
@@ -1,330 +1,581 @@
-use std::fs::File;
-use std::io::{BufReader, Write};
+use std::cmp::Reverse;
+use std::fs::{create_dir_all, File};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::{Duration, SystemTime};
-use rss::Channel;
-use crate::structs::Podcast;
-use crate::TXTD;
-use crate::utils::*;
-
-// This function detects if a podcast's cache is outdated and updates it.
-pub fn do_autocache(target_index: usize, mut podcasts: Vec<Podcast>) -> Vec<Podcast>
+use rss::{Channel, Item};
+use crate::discovery::{discover_feeds, FeedChooser};
+use crate::auto_download::AutoDownloadEdit;
+use crate::auth::{FeedAuth, FeedCredentials, SecretHeader};
+use crate::error::{Error, Result};
+use crate::http::{HttpClient, HttpRequest, HttpResponse, NetworkOverrides};
+use crate::structs::{FeedMoveReason, Podcast, PreviousFeedUrl};
+use crate::podcast_namespace::*;
+use crate::feed_fields::*;
+use crate::utils::{alias_from_title, parse_pub_date, find_podcast, normalize_alias, normalize_tags, validate_alias};
+
+// Everything needed to register a podcast with 'add_podcast'. The interval is in hours. An empty alias is made up from
+// the feed's title, and an empty download path becomes a folder named after the alias inside 'download_root'.
+// The feed URL can also be a web page that links to the feed. If it links to several, 'choose_feed' picks one of them by
+// index, and leaving it unset or choosing nothing fails with the list of feeds instead.
+#[derive(Default)]
+pub struct NewPodcast
 {
-    let mut target_podcast = &mut podcasts[target_index];
+    pub alias: String,
+    pub feed_url: String,
+    pub download_path: PathBuf,
+    pub download_root: Option<PathBuf>,
+    pub interval: usize,
+    pub is_locked: bool,
+    pub network: NetworkOverrides,
+    pub credentials: FeedCredentials,
+    pub tags: Vec<String>,
+    pub choose_feed: Option<FeedChooser>
+}
 
-    if target_podcast.is_locked {return podcasts}
+// Optional changes for 'edit_podcast'. Anything left as 'None' is kept as-is. Network overrides are merged into the
+// podcast's existing ones, after clearing them if 'reset_network' is set. Headers work the same way, replacing any
+// existing header with the same name, and 'Some(FeedAuth::None)' removes the podcast's authentication. Auto-download
+// rules are changed one by one, see 'AutoDownload::apply'.
+#[derive(Clone, Default)]
+pub struct PodcastEdit
+{
+    pub alias: Option<String>,
+    pub feed_url: Option<String>,
+    pub download_path: Option<PathBuf>,
+    pub interval: Option<usize>,
+    pub network: NetworkOverrides,
+    pub reset_network: bool,
+    pub auth: Option<FeedAuth>,
+    pub headers: Vec<SecretHeader>,
+    pub reset_headers: bool,
+    pub auto_download: AutoDownloadEdit
+}
 
-    if let Ok(cache_age) = SystemTime::now().duration_since(target_podcast.cache_time)
+impl PodcastEdit
+{
+    pub fn is_empty(&self) -> bool
     {
-        if cache_age > target_podcast.update_interval
-        {
-            println!("{} This podcast's cache is outdated, updating...", TXTD.important);
-            if let Ok(new_feed_raw) = reqwest::blocking::get(target_podcast.feed_url.clone())
-            {
-                if let Ok(updated_feed) = Channel::read_from(BufReader::new(new_feed_raw))
-                {
-                    target_podcast.cache_content = updated_feed.to_string();
-                    target_podcast.cache_time = SystemTime::now();
+        self.alias.is_none() && self.feed_url.is_none() && self.download_path.is_none() && self.interval.is_none()
+            && self.network.is_empty() && !self.reset_network && self.auth.is_none() && self.headers.is_empty() && !self.reset_headers
+            && self.auto_download.is_empty()
+    }
+}
+
 
-                    write_to_bin(&podcasts, get_storage_path().as_path()).unwrap();
+// A feed that said it lives somewhere else now, as noticed by 'update_podcast'.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedMove
+{
+    pub old_url: String,
+    pub new_url: String,
+    pub reason: FeedMoveReason
+}
 
-                    println!("{} Cache updated!", TXTD.completion)
-                }
+// Where a feed has moved to, and why, if it has.
+type MovedTo = Option<(String, FeedMoveReason)>;
 
-                else { println!("{} Failed to update cache automatically, due to receiving an invalid feed.", TXTD.important) }
-            }
 
-            else { println!("{} Failed to update cache automatically, due to receiving an invalid feed.", TXTD.important) }
+pub fn fetch_feed(client: &dyn HttpClient, feed_url: &str) -> Result<Channel>
+{
+    fetch_feed_response(client, feed_url).map(|(feed, _)| feed)
+}
+
+// Fetches the feed behind a URL, which is either the URL itself or the feed that a web page links to. Returns the feed's
+// URL along with what 'fetch_feed_with_move' does.
+fn fetch_or_discover_feed(client: &dyn HttpClient, url: &str, choose_feed: Option<FeedChooser>) -> Result<(String, Channel, MovedTo)>
+{
+    let response = request_ok(client, url)?;
+    let parse_error = match Channel::read_from(response.body.as_slice())
+    {
+        Ok(feed) =>
+        {
+            let (feed, moved_to) = follow_feed_move(client, url, feed, &response)?;
+            return Ok((url.to_string(), feed, moved_to));
         }
-    }
+        Err(error) => Error::Parse(format!("Failed to parse RSS feed from '{}': {}", url, error))
+    };
 
-    // Handles the current time being before the cache's timestamp.
-    else
+    // Podclaw only reads RSS, so Atom feeds are only offered when there's nothing else.
+    let mut candidates = discover_feeds(&String::from_utf8_lossy(&response.body), &response.url);
+    if candidates.iter().any(|candidate| !candidate.is_atom) { candidates.retain(|candidate| !candidate.is_atom) }
+
+    let feed_url = match candidates.len()
     {
-        if let Ok(new_feed_raw) = reqwest::blocking::get(target_podcast.feed_url.clone())
+        0 => return Err(parse_error),
+        1 => candidates.remove(0).url,
+        _ => match choose_feed.and_then(|choose_feed| choose_feed(&candidates)).and_then(|index| candidates.get(index))
         {
-            if let Ok(updated_feed) = Channel::read_from(BufReader::new(new_feed_raw))
+            Some(candidate) => candidate.url.clone(),
+            None =>
             {
-                target_podcast.cache_content = updated_feed.to_string();
-                target_podcast.cache_time = SystemTime::now();
-                write_to_bin(&podcasts, get_storage_path().as_path()).unwrap();
+                let listing: Vec<String> = candidates.iter().map(|candidate| format!("  {}", candidate.description())).collect();
+                return Err(Error::Invalid(format!("'{}' links to several feeds. Run 'add' again with one of these:\n{}", url, listing.join("\n"))));
             }
         }
+    };
 
-        else { println!("{} Failed to update cache automatically, due to receiving an invalid feed.", TXTD.important) }
-    }
-
-    return podcasts;
+    let (feed, moved_to) = fetch_feed_with_move(client, &feed_url)?;
+    Ok((feed_url, feed, moved_to))
 }
 
+// Fetches a feed, along with where it's moved to, if it has. A new URL from 'itunes:new-feed-url' is only trusted once
+// it's been fetched successfully, so a broken tag can't strand a podcast.
+fn fetch_feed_with_move(client: &dyn HttpClient, feed_url: &str) -> Result<(Channel, MovedTo)>
+{
+    let (feed, response) = fetch_feed_response(client, feed_url)?;
+    follow_feed_move(client, feed_url, feed, &response)
+}
 
-pub fn add_podcast(new_alias: String, new_link: String, download_path: PathBuf, interval: usize, should_lock: bool, mut podcasts: Vec<Podcast>)
+fn follow_feed_move(client: &dyn HttpClient, feed_url: &str, feed: Channel, response: &HttpResponse) -> Result<(Channel, MovedTo)>
 {
-    for podcasts in &podcasts
+    let final_url = response.permanent_url().unwrap_or(feed_url).to_string();
+
+    let new_feed_url = feed.itunes_ext.as_ref()
+        .and_then(|itunes| itunes.new_feed_url.as_deref())
+        .map(str::trim)
+        .filter(|new_url| (new_url.starts_with("http://") || new_url.starts_with("https://")) && *new_url != final_url && *new_url != feed_url);
+
+    if let Some(new_url) = new_feed_url
     {
-        if new_alias == podcasts.alias
+        if let Ok((new_feed, new_response)) = fetch_feed_response(client, new_url)
         {
-            println!("{} Alias already in use.", TXTD.error);
-            return;
+            let new_url = new_response.permanent_url().unwrap_or(new_url).to_string();
+            return Ok((new_feed, Some((new_url, FeedMoveReason::NewFeedUrl))));
         }
     }
 
-    println!("{} Registering new podcast with this alias: \'{}\'", TXTD.general, new_alias);
-    let mut new_podcast = Podcast::new();
-    new_podcast.alias = new_alias;
+    let moved_to = response.permanent_url().map(|new_url| (new_url.to_string(), FeedMoveReason::Redirect));
+    Ok((feed, moved_to))
+}
 
+fn fetch_feed_response(client: &dyn HttpClient, feed_url: &str) -> Result<(Channel, HttpResponse)>
+{
+    let response = request_ok(client, feed_url)?;
 
+    let feed = Channel::read_from(response.body.as_slice())
+        .map_err(|error| Error::Parse(format!("Failed to parse RSS feed from '{}': {}", feed_url, error)))?;
 
-    if let Ok(new_feed_raw) = reqwest::blocking::get(new_link.clone())
+    Ok((feed, response))
+}
+
+// A cache is outdated once its interval has passed, or if its timestamp is somehow in the future.
+pub fn is_cache_outdated(podcast: &Podcast) -> bool
+{
+    match SystemTime::now().duration_since(podcast.cache_time)
     {
-        if let Ok(new_feed) = Channel::read_from(BufReader::new(new_feed_raw))
-        {
-            println!("{} Podcast will use this link: {}", TXTD.general, italicize!(format!("\'{}\'", new_link)));
-            new_podcast.feed_url = new_link;
+        Ok(cache_age) => cache_age > podcast.update_interval,
+        Err(_) => true
+    }
+}
+
+// When the cache will next be refreshed automatically. Locked podcasts never are.
+pub fn next_update(podcast: &Podcast) -> Option<SystemTime>
+{
+    if podcast.is_locked { return None }
+    Some(podcast.cache_time + podcast.update_interval)
+}
 
-            println!("{} This podcast will save its downloaded files to: {}", TXTD.general, italicize!(format!("\'{}\'", download_path.to_str().unwrap())));
-            new_podcast.download_path = download_path;
+// This function detects if a podcast's cache is outdated and updates it. Returns whether an update happened.
+pub fn do_autocache(client: &dyn HttpClient, podcast: &mut Podcast) -> Result<bool>
+{
+    if podcast.is_locked || !is_cache_outdated(podcast) { return Ok(false) }
 
-            println!("{} This podcast will keep its cache for this many hours: {}", TXTD.general, italicize!(format!("\'{}\'", interval)));
-            new_podcast.update_interval = Duration::from_secs( (interval as u64 * 60) * 60 );
+    update_podcast(client, podcast)?;
+    Ok(true)
+}
 
-            new_podcast.cache_time = SystemTime::now();
-            new_podcast.cache_content = new_feed.to_string();
-            println!("{} Parsed RSS feed and created initial cache.", TXTD.general);
 
-            if should_lock { new_podcast.is_locked = true }
-            else { new_podcast.is_locked = false }
-        }
+// Feed requests for private podcasts need an 'AuthenticatedClient' built from the podcast's credentials.
+pub fn add_podcast<'a>(client: &dyn HttpClient, new: NewPodcast, podcasts: &'a mut Vec<Podcast>) -> Result<&'a Podcast>
+{
+    let alias = normalize_alias(&new.alias);
+    if !alias.is_empty() { ensure_alias_available(&alias, podcasts)? }
+    let tags = normalize_tags(&new.tags)?;
 
-        else
-        {
-            println!("{} Failed to parse RSS feed.", TXTD.error);
-            return;
-        }
-    }
+    let (feed_url, new_feed, moved_to) = fetch_or_discover_feed(client, &new.feed_url, new.choose_feed)?;
 
-    else
+    let mut new_podcast = Podcast::new();
+    new_podcast.alias = if alias.is_empty() { alias_from_title(&channel_title(&new_feed).unwrap_or_default(), podcasts) }
+    else { alias };
+    new_podcast.feed_url = feed_url;
+    new_podcast.download_path = match new.download_root.filter(|_| new.download_path.as_os_str().is_empty())
     {
-        println!("{} Failed to request feed from provided link.", TXTD.error);
-        return;
-    }
+        Some(download_root) => download_root.join(&new_podcast.alias),
+        None => new.download_path
+    };
+    new_podcast.update_interval = hours_to_duration(new.interval);
+    new_podcast.cache_time = SystemTime::now();
+    new_podcast.cache_content = new_feed.to_string();
+    new_podcast.is_locked = new.is_locked;
+    new_podcast.network = new.network;
+    new_podcast.credentials = new.credentials;
+    new_podcast.tags = tags;
+
+    if let Some((new_url, reason)) = moved_to.filter(|_| !new.is_locked) { move_feed(&mut new_podcast, new_url, reason) }
 
-    println!("{} Writing podcast and cache to storage...", TXTD.general);
     podcasts.push(new_podcast);
-    write_to_bin(&podcasts, get_storage_path().as_path()).expect("Failed to write to storage!");
-
-    println!("{} Done!", TXTD.completion);
+    Ok(podcasts.last().unwrap())
 }
 
 
-pub fn remove_podcast(target_index: usize, mut podcasts: Vec<Podcast>)
+// Locked podcasts can be removed too, since locking only keeps a podcast from being updated or edited.
+pub fn remove_podcast(target_index: usize, podcasts: &mut Vec<Podcast>) -> Podcast
 {
-    println!("{} Removing {} from podcasts...", TXTD.general, italicize!(format!("\'{}\'", podcasts[target_index].alias)));
-
-    podcasts.swap_remove(target_index);
-    write_to_bin(&podcasts, get_storage_path().as_path()).expect("Failed to write to storage!");
-
-    println!("{} Done!", TXTD.completion);
+    podcasts.swap_remove(target_index)
 }
 
 
-pub fn edit_podcast(target_index: usize, new_alias_opt: Option<String>, new_link_opt: Option<String>, new_dl_path_opt: Option<PathBuf>, new_interval_opt: Option<usize>, mut podcasts: Vec<Podcast>)
+// Applies an edit to a podcast. Returns whether anything changed.
+pub fn edit_podcast(target_index: usize, podcasts: &mut [Podcast], edit: PodcastEdit) -> Result<bool>
 {
-    let mut podcast = &mut podcasts[target_index];
+    ensure_unlocked(&podcasts[target_index])?;
 
-    if podcast.is_locked
-    {
-        println!("{} Podcast is locked.", TXTD.error);
-        return;
-    }
+    if edit.is_empty() { return Ok(false) }
 
-    println!("{} Editing {}...", TXTD.general, italicize!(format!("\'{}\'", podcast.alias)));
+    // Checked before anything else changes, so a bad title pattern leaves the podcast as it was.
+    let mut auto_download = podcasts[target_index].auto_download.clone();
+    auto_download.apply(edit.auto_download)?;
 
-    if let Some(new_alias) = new_alias_opt.clone()
+    if let Some(new_alias) = edit.alias.map(|new_alias| normalize_alias(&new_alias)).filter(|new_alias| *new_alias != podcasts[target_index].alias)
     {
-        podcast.alias = new_alias.clone();
-        println!("{} Changed alias to {}!", TXTD.general, italicize!(format!("\'{}\'", new_alias)));
+        ensure_alias_available(&new_alias, podcasts)?;
+        podcasts[target_index].alias = new_alias;
     }
 
-    if let Some(new_link) = new_link_opt.clone()
-    {
-        podcast.feed_url = new_link.clone();
-        println!("{} Changed feed link to {}!", TXTD.general, italicize!(format!("\'{}\'", new_link)));
-    }
+    let podcast = &mut podcasts[target_index];
 
-    if let Some(new_dl_path) = new_dl_path_opt.clone()
+    if let Some(new_link) = edit.feed_url.filter(|new_link| *new_link != podcast.feed_url)
     {
-        podcast.download_path = new_dl_path.clone();
-        println!("{} Changed download path to {}!", TXTD.general, italicize!(format!("\'{}\'", new_dl_path.to_str().unwrap())));
+        move_feed(podcast, new_link, FeedMoveReason::Edit);
     }
+    if let Some(new_dl_path) = edit.download_path { podcast.download_path = new_dl_path }
+    if let Some(new_interval) = edit.interval { podcast.update_interval = hours_to_duration(new_interval) }
 
-    if let Some(new_interval) = new_interval_opt.clone()
-    {
-        podcast.update_interval = Duration::from_secs( (new_interval as u64 * 60) * 60 );
-        println!("{} Changed update interval to {}!", TXTD.general, italicize!(format!("\'{}\'", new_interval)));
-    }
+    if edit.reset_network { podcast.network = NetworkOverrides::default() }
+    merge_network_overrides(&mut podcast.network, edit.network);
 
-    if new_alias_opt.is_none() && new_link_opt.is_none() && new_dl_path_opt.is_none() && new_interval_opt.is_none()
-    {
-        println!("{} No changes made.", TXTD.important);
-    }
+    if let Some(new_auth) = edit.auth { podcast.credentials.auth = new_auth }
+    if edit.reset_headers { podcast.credentials.headers.clear() }
+    merge_headers(&mut podcast.credentials.headers, edit.headers);
 
-    else
-    {
-        write_to_bin(&podcasts, get_storage_path().as_path()).unwrap();
-        println!("{} Successfully edited podcast!", TXTD.completion);
-    }
+    podcast.auto_download = auto_download;
+
+    Ok(true)
 }
 
-pub fn update_podcast(target_index: usize, mut podcasts: Vec<Podcast>)
+// Refreshes a podcast's cache. If the feed has moved, the podcast follows it, and the move is returned so it can be
+// reported. The old URL is kept in the podcast's history.
+pub fn update_podcast(client: &dyn HttpClient, podcast: &mut Podcast) -> Result<Option<FeedMove>>
 {
-    let mut target_podcast = &mut podcasts[target_index];
+    ensure_unlocked(podcast)?;
 
-    if target_podcast.is_locked
-    {
-        println!("{} Podcast is locked.", TXTD.error);
-        return;
-    }
+    let (updated_feed, moved_to) = fetch_feed_with_move(client, &podcast.feed_url)?;
+    podcast.cache_content = updated_feed.to_string();
+    podcast.cache_time = SystemTime::now();
 
-    println!("{} Updating podcast {}...", TXTD.general, italicize!(format!("\'{}\'", target_podcast.alias)));
-    if let Ok(new_feed_raw) = reqwest::blocking::get(target_podcast.feed_url.clone())
+    Ok(moved_to.map(|(new_url, reason)|
     {
-        if let Ok(updated_feed) = Channel::read_from(BufReader::new(new_feed_raw))
-        {
-            target_podcast.cache_content = updated_feed.to_string();
-            target_podcast.cache_time = SystemTime::now();
+        let old_url = podcast.feed_url.clone();
+        move_feed(podcast, new_url.clone(), reason);
+        FeedMove { old_url, new_url, reason }
+    }))
+}
 
-            write_to_bin(&podcasts, get_storage_path().as_path()).unwrap();
+// Adds tags to a podcast. Returns the ones it didn't have yet.
+pub fn tag_podcast(podcast: &mut Podcast, tags: &[String]) -> Result<Vec<String>>
+{
+    ensure_unlocked(podcast)?;
 
-            println!("{} Cache updated!", TXTD.completion)
-        } else { println!("{} Failed to update cache, due to receiving an invalid feed.", TXTD.important) }
-    }
+    let new_tags: Vec<String> = normalize_tags(tags)?.into_iter().filter(|tag| !podcast.tags.contains(tag)).collect();
+    podcast.tags.extend(new_tags.iter().cloned());
+    Ok(new_tags)
 }
 
-pub fn lock_podcast(target_index: usize, mut podcasts: Vec<Podcast>)
+// Removes tags from a podcast. Returns the ones it actually had.
+pub fn untag_podcast(podcast: &mut Podcast, tags: &[String]) -> Result<Vec<String>>
 {
-    let mut podcast = &mut podcasts[target_index];
+    ensure_unlocked(podcast)?;
 
-    if podcast.is_locked
-    {
-        podcast.is_locked = false;
-        println!("{} Successfully unlocked podcast!", TXTD.completion);
-    }
+    let removed_tags: Vec<String> = normalize_tags(tags)?.into_iter().filter(|tag| podcast.tags.contains(tag)).collect();
+    podcast.tags.retain(|tag| !removed_tags.contains(tag));
+    Ok(removed_tags)
+}
+
+
+// Marks episodes as played or unplayed. Returns how many of them changed. Locked podcasts can still be listened to.
+pub fn mark_played(podcast: &mut Podcast, episodes: &[&Item], played: bool) -> usize
+{
+    let mut changed = 0;
 
-    else
+    for episode in episodes
     {
-        podcast.is_locked = true;
-        println!("{} Successfully locked podcast!", TXTD.completion);
+        let episode_id = episode_id(episode);
+        if is_played(podcast, episode) == played { continue }
+
+        if played { podcast.played_episodes.push(episode_id) }
+        else { podcast.played_episodes.retain(|played_id| *played_id != episode_id) }
+        changed += 1;
     }
 
-    write_to_bin(&podcasts, get_storage_path().as_path()).unwrap();
+    changed
+}
+
+pub fn is_played(podcast: &Podcast, episode: &Item) -> bool
+{
+    podcast.played_episodes.contains(&episode_id(episode))
+}
+
+// Episodes that can be downloaded but haven't been marked as played.
+pub fn unplayed_count(podcast: &Podcast, feed: &Channel) -> usize
+{
+    feed.items.iter().filter(|episode| episode_enclosure(episode).is_some() && !is_played(podcast, episode)).count()
 }
 
 
-pub fn inspect_podcast(target_index: usize, episode_index: usize, do_episode: bool, do_normal_episode_order: bool, podcasts: Vec<Podcast>)
+// Toggles a podcast's lock. Returns whether it's now locked.
+pub fn lock_podcast(podcast: &mut Podcast) -> bool
 {
-    let feed = Channel::from_str
-        (
-            podcasts.get(target_index).unwrap()
-                .cache_content.as_str()
-        ).unwrap();
+    podcast.is_locked = !podcast.is_locked;
+    podcast.is_locked
+}
 
-    if do_episode
-    {
-        let mut episodes = feed.items;
-        if !do_normal_episode_order { episodes.reverse() }
 
-        if episode_index > episodes.len() - 1
+pub fn load_cached_feed(podcast: &Podcast) -> Result<Channel>
+{
+    Channel::from_str(podcast.cache_content.as_str())
+        .map_err(|_| Error::Parse(format!("The cached feed for '{}' can't be parsed. Try running the 'update' command!", podcast.alias)))
+}
+
+// How episode indices are counted. Feeds don't always list their episodes by date, so 'Oldest' and 'Newest' sort them by
+// their pubDate, while 'Feed' keeps the feed's own order.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EpisodeOrder
+{
+    #[default]
+    Oldest,
+    Newest,
+    Feed
+}
+
+impl FromStr for EpisodeOrder
+{
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self>
+    {
+        match name
         {
-            println!("{} Episode index is out of bounds.", TXTD.error);
-            return;
+            "oldest" => Ok(EpisodeOrder::Oldest),
+            "newest" => Ok(EpisodeOrder::Newest),
+            "feed" => Ok(EpisodeOrder::Feed),
+            _ => Err(Error::Invalid(format!("Unknown episode order '{}'. It should be 'oldest', 'newest' or 'feed'.", name)))
         }
+    }
+}
 
-        let target_episode = episodes.get(episode_index).unwrap();
+// Episodes without a date that can be read go last, in the order the feed lists them, so a broken date doesn't shift
+// the indices of every other episode. Episodes with the same date keep the order they had before dates were sorted by,
+// which is the reverse of the feed's when the oldest come first.
+pub fn ordered_episodes(feed: &Channel, order: EpisodeOrder) -> Vec<Item>
+{
+    ordered_indices(feed, order).into_iter().map(|item_index| feed.items[item_index].clone()).collect()
+}
 
-        println!("{} Displaying details for the requested episode...", TXTD.general);
+// The index each episode of 'ordered_episodes' is named with in file names, which is its index oldest first. That way
+// an episode's files have the same name whichever order it was picked in.
+pub fn file_indices(feed: &Channel, order: EpisodeOrder) -> Vec<usize>
+{
+    let mut oldest_first = vec![0; feed.items.len()];
+    for (file_index, item_index) in ordered_indices(feed, EpisodeOrder::Oldest).into_iter().enumerate() { oldest_first[item_index] = file_index }
 
-        println!("  {} {}\n", make_bold!("Name: "), italicize!(format!("\'{}\'", target_episode.title.clone().unwrap() )));
-        println!("  {} {}\n", make_bold!("Index: "), italicize!(format!("{}", episode_index)));
-        println!("  {} {}\n", make_bold!("Description: "), italicize!(format!("\'{}\'", target_episode.description.clone().unwrap() )));
-        println!("  {} {}", make_bold!("Link: "), italicize!(format!("\'{}\'", target_episode.enclosure.clone().unwrap().url )));
-    }
+    ordered_indices(feed, order).into_iter().map(|item_index| oldest_first[item_index]).collect()
+}
+
+// The positions of the feed's items in the given order.
+fn ordered_indices(feed: &Channel, order: EpisodeOrder) -> Vec<usize>
+{
+    if order == EpisodeOrder::Feed { return (0..feed.items.len()).collect() }
 
-    else
+    let mut dated = Vec::new();
+    let mut undated = Vec::new();
+    for (item_index, episode) in feed.items.iter().enumerate()
     {
-        println!("{} Displaying details for the requested series...", TXTD.general);
+        match episode.pub_date.as_deref().and_then(parse_pub_date)
+        {
+            Some(date) => dated.push((date, item_index)),
+            None => undated.push(item_index)
+        }
+    }
 
-        println!("  {} {}\n", make_bold!("Name: "), italicize!(format!("\'{}\'", feed.title )));
-        println!("  {} {}\n", make_bold!("Creator(s): "), italicize!(format!("\'{}\'", feed.itunes_ext.unwrap().author.unwrap() )));
-        println!("  {} {}", make_bold!("Description: "), italicize!(format!("\'{}\'", feed.description )));
+    if order == EpisodeOrder::Oldest
+    {
+        dated.reverse();
+        dated.sort_by_key(|(date, _)| *date);
     }
+
+    else { dated.sort_by_key(|(date, _)| Reverse(*date)) }
+
+    dated.into_iter().map(|(_, item_index)| item_index).chain(undated).collect()
 }
 
-pub fn get_episode(target_index: usize, episode_index: usize, do_normal_episode_order: bool, podcasts: Vec<Podcast>)
+pub fn select_episode(episodes: &[Item], episode_index: usize) -> Result<&Item>
 {
-    let podcast = podcasts.get(target_index).unwrap();
-    let feed = Channel::from_str
-        (
-            podcast.cache_content.as_str()
-        ).unwrap();
+    episodes.get(episode_index)
+        .ok_or_else(|| Error::NotFound(String::from("Episode index is out of bounds.")))
+}
 
-    let mut episodes = feed.items;
-    if !do_normal_episode_order { episodes.reverse() }
 
-    if episode_index < episodes.len()
+// Where an episode's audio goes, named by a template like the config's 'filename_template'. Chapters and transcripts
+// share the same stem. The index is the episode's oldest-first index, see 'file_indices'.
+pub fn episode_download_path(podcast: &Podcast, episode_index: usize, episode: &Item, template: &str) -> PathBuf
+{
+    podcast.download_path.join(Path::new(format!("{}.mp3", episode_file_stem(podcast, episode_index, episode, template)).as_str()))
+}
+
+pub fn episode_audio_url(episode: &Item) -> Result<String>
+{
+    episode_enclosure(episode)
+        .map(|enclosure| enclosure.url.clone())
+        .ok_or_else(|| Error::NotFound(String::from("This episode has no audio or video attached, so it can't be downloaded.")))
+}
+
+// The chapters file to download for an episode, if it publishes one.
+pub fn chapters_download(podcast: &Podcast, episode_index: usize, episode: &Item, namespace: &PodcastNamespace, template: &str) -> Option<(String, PathBuf)>
+{
+    namespace.chapters.as_ref().map(|chapters|
     {
-        let target_episode = episodes.get(episode_index).unwrap();
-        let full_download_path = podcast.download_path.join( Path::new( format!("[{} - {}] {}.mp3", podcast.alias, episode_index, target_episode.title.clone().unwrap()).as_str() ));
+        let path = podcast.download_path.join(format!("{}.chapters.json", episode_file_stem(podcast, episode_index, episode, template)));
+        (chapters.url.clone(), path)
+    })
+}
+
+// The SRT and VTT transcripts to download for an episode. Other transcript formats are skipped. Each transcript's
+// language goes into its name, like '.en.srt', which is also how media players find them, and transcripts that would
+// still share a name are numbered.
+pub fn transcript_downloads(podcast: &Podcast, episode_index: usize, episode: &Item, namespace: &PodcastNamespace, template: &str) -> Vec<(String, PathBuf)>
+{
+    let stem = episode_file_stem(podcast, episode_index, episode, template);
+    let mut downloads: Vec<(String, PathBuf)> = Vec::new();
 
-        println!("{} Downloading {}...", TXTD.general, italicize!( format!("\'{}\'", full_download_path.to_str().unwrap()) ));
-        if let Ok(target_audio) = reqwest::blocking::get(target_episode.enclosure.clone().unwrap().url)
+    for transcript in &namespace.transcripts
+    {
+        let Some(extension) = transcript.subtitle_extension() else { continue };
+        let language: Option<String> = transcript.language.as_deref()
+            .map(|language| language.chars().filter(|character| character.is_ascii_alphanumeric() || *character == '-').collect())
+            .filter(|language: &String| !language.is_empty());
+        let base = match language
         {
-            let mut new_file = File::create(full_download_path)
-                .expect("Failed to create new file!");
+            Some(language) => format!("{}.{}", stem, language),
+            None => stem.clone()
+        };
 
-            new_file.write_all( target_audio.bytes().unwrap().to_vec().as_ref()).expect("Failed to write to file!");
-            println!("{} Done!", TXTD.completion);
+        let mut path = podcast.download_path.join(format!("{}.{}", base, extension));
+        let mut number = 2;
+        while downloads.iter().any(|(_, taken)| *taken == path)
+        {
+            path = podcast.download_path.join(format!("{}.{}.{}", base, number, extension));
+            number += 1;
         }
 
-        else { println!("{} Failed to get audio file from the internet.", TXTD.error) }
+        downloads.push((transcript.url.clone(), path));
     }
 
-    else { println!("{} Episode index is out of bounds.", TXTD.error) }
+    downloads
 }
 
-
-pub fn list_podcasts_or_episodes(alias: Option<String>, do_normal_episode_order: bool, podcasts: Vec<Podcast>)
+// Downloads a file and writes it to the given path.
+pub fn download_file(client: &dyn HttpClient, url: &str, destination: &Path) -> Result<()>
 {
-    if alias.is_none()
+    let response = request_ok(client, url)?;
+
+    // Podcasts under the download root get their folder with their first episode.
+    if let Some(parent) = destination.parent().filter(|parent| !parent.as_os_str().is_empty())
     {
-        println!("{} Listing all registered podcasts...", TXTD.general);
-        for (podcast_index, podcast) in podcasts.iter().enumerate()
-        {
-            println!("  {} {}", make_bold!(format!("#{}:", podcast_index)), italicize!(format!("\'{}\'", podcast.alias)));
-        }
+        create_dir_all(parent).map_err(|error| Error::Storage(format!("Failed to create '{}': {}", parent.display(), error)))?;
     }
 
-    else
+    let mut new_file = File::create(destination)
+        .map_err(|error| Error::Storage(format!("Failed to create '{}': {}", destination.display(), error)))?;
+
+    new_file.write_all(&response.body)
+        .map_err(|error| Error::Storage(format!("Failed to write to '{}': {}", destination.display(), error)))
+}
+
+
+// Makes a request, treating any non-2xx answer as a failure.
+fn request_ok(client: &dyn HttpClient, url: &str) -> Result<HttpResponse>
+{
+    let response = client.get(&HttpRequest::new(url))?;
+
+    if response.is_success() { return Ok(response) }
+
+    // Private feeds answer like this when their credentials are missing or wrong.
+    let hint = match response.status
     {
-        if let Some(target_index) = find_podcast(&alias.unwrap().to_lowercase(), &podcasts)
-        {
-            let feed = Channel::from_str
-                (
-                    podcasts.get(target_index).unwrap()
-                        .cache_content.as_str()
-                ).unwrap();
+        401 | 403 => " Check the podcast's credentials.",
+        410 => " It's been removed for good, so this podcast may have ended or moved without a redirect.",
+        _ => ""
+    };
+    Err(Error::Network(format!("Request to '{}' failed: the server answered with HTTP {}.{}", url, response.status, hint)))
+}
 
-            let mut episodes = feed.items;
-            if !do_normal_episode_order { episodes.reverse() }
+// Fills in a filename template. The date is the episode's publish date, or 'undated' without one.
+fn episode_file_stem(podcast: &Podcast, episode_index: usize, episode: &Item, template: &str) -> String
+{
+    let date = episode.pub_date.as_deref().and_then(parse_pub_date)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or(String::from("undated"));
+
+    template
+        .replace("{alias}", &podcast.alias)
+        .replace("{index}", &episode_index.to_string())
+        .replace("{date}", &date)
+        .replace("{title}", &episode_file_title(episode))
+}
 
-            println!("{} Listing all episodes in the requested podcast...", TXTD.general);
-            for (episode_index, episode) in episodes.iter().enumerate()
-            {
-                println!("  {} {}", make_bold!(format!("#{}:", episode_index)), italicize!(format!("\'{}\'", episode.title.clone().unwrap())))
-            }
-        }
+fn merge_network_overrides(current: &mut NetworkOverrides, changes: NetworkOverrides)
+{
+    if changes.connect_timeout.is_some() { current.connect_timeout = changes.connect_timeout }
+    if changes.read_timeout.is_some() { current.read_timeout = changes.read_timeout }
+    if changes.retries.is_some() { current.retries = changes.retries }
+    if changes.retry_backoff.is_some() { current.retry_backoff = changes.retry_backoff }
+    if changes.user_agent.is_some() { current.user_agent = changes.user_agent }
+    if changes.proxy.is_some() { current.proxy = changes.proxy }
+}
+
+fn ensure_alias_available(alias: &str, podcasts: &[Podcast]) -> Result<()>
+{
+    validate_alias(alias)?;
+
+    if find_podcast(alias, podcasts).is_some() { Err(Error::Invalid(format!("The alias '{}' is already in use.", alias))) }
+    else { Ok(()) }
+}
+
+fn move_feed(podcast: &mut Podcast, new_url: String, reason: FeedMoveReason)
+{
+    let old_url = std::mem::replace(&mut podcast.feed_url, new_url);
+    podcast.previous_feed_urls.push(PreviousFeedUrl { url: old_url, replaced_at: SystemTime::now(), reason });
+}
 
-        else { println!("{} There is no podcast with that alias.", TXTD.error) }
+// Header names are case-insensitive, so a new header replaces any existing one with the same name.
+fn merge_headers(current: &mut Vec<SecretHeader>, changes: Vec<SecretHeader>)
+{
+    for (name, value) in changes
+    {
+        current.retain(|(existing_name, _)| !existing_name.eq_ignore_ascii_case(&name));
+        current.push((name, value));
     }
-}
\ No newline at end of file
+}
+
+fn ensure_unlocked(podcast: &Podcast) -> Result<()>
+{
+    if podcast.is_locked { Err(Error::Locked(format!("The podcast '{}' is locked.", podcast.alias))) }
+    else { Ok(()) }
+}
+
+fn hours_to_duration(hours: usize) -> Duration
+{
+    Duration::from_secs( (hours as u64 * 60) * 60 )
+}
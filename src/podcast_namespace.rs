@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use rss::{Channel, Item};
+use rss::extension::{Extension, ExtensionMap};
+use serde::Serialize;
+
+// The URI of the Podcasting 2.0 namespace. Feeds nearly always bind it to the 'podcast' prefix, but it isn't guaranteed.
+pub const PODCAST_NAMESPACE_URI: &str = "https://podcastindex.org/namespace/1.0";
+
+
+#[derive(Serialize)]
+pub struct Chapters
+{
+    pub url: String,
+    pub mime_type: String
+}
+
+#[derive(Serialize)]
+pub struct Transcript
+{
+    pub url: String,
+    pub mime_type: String,
+    pub language: Option<String>,
+    pub rel: Option<String>
+}
+
+#[derive(Serialize)]
+pub struct Person
+{
+    pub name: String,
+    pub role: Option<String>,
+    pub group: Option<String>,
+    pub href: Option<String>
+}
+
+pub struct Season
+{
+    pub number: String,
+    pub name: Option<String>
+}
+
+pub struct EpisodeNumber
+{
+    pub number: String,
+    pub display: Option<String>
+}
+
+#[derive(Serialize)]
+pub struct Funding
+{
+    pub url: String,
+    pub text: Option<String>
+}
+
+// All the Podcasting 2.0 tags Podclaw understands. Channels only ever fill 'persons' and 'funding'.
+#[derive(Default)]
+pub struct PodcastNamespace
+{
+    pub chapters: Option<Chapters>,
+    pub transcripts: Vec<Transcript>,
+    pub persons: Vec<Person>,
+    pub season: Option<Season>,
+    pub episode: Option<EpisodeNumber>,
+    pub funding: Vec<Funding>
+}
+
+impl Transcript
+{
+    // Chapters and transcripts can be published in several formats, but only subtitles are worth saving next to the audio.
+    pub fn subtitle_extension(&self) -> Option<&'static str>
+    {
+        match self.mime_type.as_str()
+        {
+            "application/srt" | "application/x-subrip" | "text/srt" => Some("srt"),
+            "text/vtt" => Some("vtt"),
+            _ => None
+        }
+    }
+}
+
+
+// Finds the prefix a feed bound the Podcasting 2.0 namespace to.
+pub fn namespace_prefix(channel: &Channel) -> String
+{
+    for (prefix, uri) in &channel.namespaces
+    {
+        if uri.trim_end_matches('/') == PODCAST_NAMESPACE_URI.trim_end_matches('/')
+        {
+            return prefix.clone();
+        }
+    }
+
+    String::from("podcast")
+}
+
+pub fn parse_channel_namespace(channel: &Channel) -> PodcastNamespace
+{
+    parse_extensions(&channel.extensions, &namespace_prefix(channel))
+}
+
+pub fn parse_item_namespace(item: &Item, prefix: &str) -> PodcastNamespace
+{
+    parse_extensions(&item.extensions, prefix)
+}
+
+
+fn parse_extensions(extensions: &ExtensionMap, prefix: &str) -> PodcastNamespace
+{
+    let mut namespace = PodcastNamespace::default();
+
+    let tags = match extensions.get(prefix)
+    {
+        Some(tags) => tags,
+        None => return namespace
+    };
+
+    if let Some(chapters) = first_tag(tags, "chapters")
+    {
+        if let Some(url) = chapters.attrs.get("url")
+        {
+            namespace.chapters = Some(Chapters
+            {
+                url: url.clone(),
+                mime_type: attribute_or(chapters, "type", "application/json+chapters")
+            });
+        }
+    }
+
+    for transcript in tags.get("transcript").into_iter().flatten()
+    {
+        if let Some(url) = transcript.attrs.get("url")
+        {
+            namespace.transcripts.push(Transcript
+            {
+                url: url.clone(),
+                mime_type: attribute_or(transcript, "type", ""),
+                language: transcript.attrs.get("language").cloned(),
+                rel: transcript.attrs.get("rel").cloned()
+            });
+        }
+    }
+
+    for person in tags.get("person").into_iter().flatten()
+    {
+        if let Some(name) = tag_text(person)
+        {
+            namespace.persons.push(Person
+            {
+                name,
+                role: person.attrs.get("role").cloned(),
+                group: person.attrs.get("group").cloned(),
+                href: person.attrs.get("href").cloned()
+            });
+        }
+    }
+
+    if let Some(season) = first_tag(tags, "season")
+    {
+        if let Some(number) = tag_text(season)
+        {
+            namespace.season = Some(Season { number, name: season.attrs.get("name").cloned() });
+        }
+    }
+
+    if let Some(episode) = first_tag(tags, "episode")
+    {
+        if let Some(number) = tag_text(episode)
+        {
+            namespace.episode = Some(EpisodeNumber { number, display: episode.attrs.get("display").cloned() });
+        }
+    }
+
+    for funding in tags.get("funding").into_iter().flatten()
+    {
+        if let Some(url) = funding.attrs.get("url")
+        {
+            namespace.funding.push(Funding { url: url.clone(), text: tag_text(funding) });
+        }
+    }
+
+    namespace
+}
+
+fn first_tag<'a>(tags: &'a BTreeMap<String, Vec<Extension>>, name: &str) -> Option<&'a Extension>
+{
+    tags.get(name).and_then(|found| found.first())
+}
+
+fn tag_text(tag: &Extension) -> Option<String>
+{
+    tag.value.as_ref()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn attribute_or(tag: &Extension, attribute: &str, fallback: &str) -> String
+{
+    tag.attrs.get(attribute).cloned().unwrap_or_else(|| fallback.to_string())
+}
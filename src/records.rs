@@ -0,0 +1,353 @@
+// Structured versions of what Podclaw prints, for '--output json' and '--output ndjson'. Field names are part of the
+// interface scripts rely on, so they only ever get added to, never renamed.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use chrono::{DateTime, Utc};
+use rss::{Channel, Item};
+use serde::Serialize;
+use crate::auth::{FeedAuth, FeedCredentials, Secret};
+use crate::error::{Error, Result};
+use crate::feed_fields::*;
+use crate::http::{redact_userinfo, NetworkOverrides};
+use crate::manage_feeds::{load_cached_feed, FeedMove};
+use crate::podcast_namespace::*;
+use crate::structs::Podcast;
+use crate::utils::{parse_duration, parse_pub_date};
+
+// How Podclaw writes its output. 'Json' prints one document when the command is done, while 'Ndjson' prints a line
+// for each record as soon as it's ready, so long batches can be followed along.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat
+{
+    #[default]
+    Text,
+    Json,
+    Ndjson
+}
+
+impl FromStr for OutputFormat
+{
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self>
+    {
+        match name
+        {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            _ => Err(Error::Invalid(format!("Unknown output format '{}'. It should be 'text', 'json' or 'ndjson'.", name)))
+        }
+    }
+}
+
+// A registered podcast and its settings. 'feed' comes from the cache, and is missing when there's no usable cache.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PodcastRecord
+{
+    pub index: usize,
+    pub alias: String,
+    pub feed_url: String,
+    pub download_path: PathBuf,
+    pub update_interval_hours: u64,
+    pub cache_updated: Option<String>,
+    pub is_locked: bool,
+    pub tags: Vec<String>,
+    pub network: NetworkOverrides,
+    pub credentials: Option<CredentialsRecord>,
+    pub previous_feed_urls: Vec<PreviousFeedUrlRecord>,
+    pub auto_download: Option<String>,
+    pub feed: Option<FeedRecord>
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FeedRecord
+{
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub link: String,
+    pub language: Option<String>,
+    pub explicit: Option<String>,
+    pub categories: Vec<String>,
+    pub episode_count: usize,
+    pub latest_episode: Option<String>
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PreviousFeedUrlRecord
+{
+    pub url: String,
+    pub replaced_at: Option<String>,
+    pub reason: String
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CredentialsRecord
+{
+    pub auth: AuthRecord,
+    pub headers: Vec<HeaderRecord>
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AuthRecord
+{
+    None,
+    Basic { username: String, password: SecretRecord },
+    Bearer { token: SecretRecord }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct HeaderRecord
+{
+    pub name: String,
+    pub value: SecretRecord
+}
+
+// Where a secret comes from. A plain value is null unless secrets are asked for, like in 'inspect --show-secrets'.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum SecretRecord
+{
+    Plain { value: Option<String> },
+    Stored { name: String }
+}
+
+// An episode with everything Podclaw knows about it. Dates are RFC 3339 when they can be parsed, and the raw pubDate
+// is kept either way.
+#[derive(Serialize)]
+pub struct EpisodeRecord
+{
+    pub index: usize,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub published: Option<String>,
+    pub pub_date: Option<String>,
+    pub guid: Option<String>,
+    pub link: Option<String>,
+    pub downloadable: bool,
+    pub enclosure: Option<EnclosureRecord>,
+    pub duration_seconds: Option<u64>,
+    pub explicit: Option<String>,
+    pub season: Option<String>,
+    pub season_name: Option<String>,
+    pub episode: Option<String>,
+    pub episode_display: Option<String>,
+    pub categories: Vec<String>,
+    pub chapters: Option<Chapters>,
+    pub transcripts: Vec<Transcript>,
+    pub persons: Vec<Person>,
+    pub funding: Vec<Funding>
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct EnclosureRecord
+{
+    pub url: String,
+    pub length: Option<u64>,
+    pub mime_type: String
+}
+
+// What happened when a command worked on one podcast. 'status' is "ok" or "failed", and failures carry their error.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ResultRecord
+{
+    pub alias: Option<String>,
+    pub action: String,
+    pub status: String,
+    pub changed: bool,
+    pub error: Option<ErrorRecord>,
+    pub feed_move: Option<FeedMoveRecord>,
+    pub files: Vec<PathBuf>
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ErrorRecord
+{
+    pub kind: String,
+    pub message: String
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FeedMoveRecord
+{
+    pub old_url: String,
+    pub new_url: String,
+    pub reason: String
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TagRecord
+{
+    pub tag: String,
+    pub podcasts: usize
+}
+
+impl ResultRecord
+{
+    pub fn succeeded(alias: &str, action: &str, changed: bool) -> Self
+    {
+        ResultRecord
+        {
+            alias: Some(alias.to_string()), ..ResultRecord::command(action, changed)
+        }
+    }
+
+    // For commands that don't work on a podcast, like 'repair' or 'secret set'.
+    pub fn command(action: &str, changed: bool) -> Self
+    {
+        ResultRecord
+        {
+            alias: None, action: action.to_string(), status: String::from("ok"), changed,
+            error: None, feed_move: None, files: Vec::new()
+        }
+    }
+
+    // For podcasts an action leaves alone on purpose, like locked ones on 'update'.
+    pub fn skipped(alias: &str, action: &str) -> Self
+    {
+        ResultRecord { status: String::from("skipped"), ..ResultRecord::succeeded(alias, action, false) }
+    }
+
+    pub fn failed(alias: &str, action: &str, error: &Error) -> Self
+    {
+        ResultRecord
+        {
+            error: Some(ErrorRecord { kind: error.kind().to_string(), message: error.to_string() }),
+            status: String::from("failed"),
+            ..ResultRecord::succeeded(alias, action, false)
+        }
+    }
+}
+
+impl From<&FeedMove> for FeedMoveRecord
+{
+    fn from(feed_move: &FeedMove) -> Self
+    {
+        FeedMoveRecord
+        {
+            old_url: feed_move.old_url.clone(),
+            new_url: feed_move.new_url.clone(),
+            reason: feed_move.reason.description().to_string()
+        }
+    }
+}
+
+
+// Secrets, and the username and password in a proxy URL, are left out unless 'show_secrets' is set, the same way
+// 'FeedCredentials::describe' hides them.
+pub fn podcast_record(index: usize, podcast: &Podcast, show_secrets: bool) -> PodcastRecord
+{
+    PodcastRecord
+    {
+        index,
+        alias: podcast.alias.clone(),
+        feed_url: podcast.feed_url.clone(),
+        download_path: podcast.download_path.clone(),
+        update_interval_hours: podcast.update_interval.as_secs() / 3600,
+        cache_updated: format_time(podcast.cache_time),
+        is_locked: podcast.is_locked,
+        tags: podcast.tags.clone(),
+        network: NetworkOverrides { proxy: podcast.network.proxy.as_deref().map(|proxy| if show_secrets { proxy.to_string() } else { redact_userinfo(proxy) }), ..podcast.network.clone() },
+        credentials: credentials_record(&podcast.credentials, show_secrets),
+        previous_feed_urls: podcast.previous_feed_urls.iter().map(|previous| PreviousFeedUrlRecord
+        {
+            url: previous.url.clone(),
+            replaced_at: format_time(previous.replaced_at),
+            reason: previous.reason.description().to_string()
+        }).collect(),
+        auto_download: podcast.auto_download.describe(),
+        feed: load_cached_feed(podcast).ok().map(|feed| feed_record(&feed))
+    }
+}
+
+pub fn feed_record(feed: &Channel) -> FeedRecord
+{
+    let latest_episode = feed.items.iter()
+        .filter_map(|item| item.pub_date.as_deref().and_then(parse_pub_date))
+        .max();
+
+    FeedRecord
+    {
+        title: channel_title(feed),
+        description: channel_description(feed),
+        author: channel_author(feed),
+        link: feed.link.clone(),
+        language: feed.language.clone(),
+        explicit: channel_explicit(feed),
+        categories: channel_categories(feed),
+        episode_count: feed.items.len(),
+        latest_episode: latest_episode.map(|date| date.to_rfc3339())
+    }
+}
+
+pub fn episode_record(feed: &Channel, index: usize, episode: &Item) -> EpisodeRecord
+{
+    let namespace = parse_item_namespace(episode, &namespace_prefix(feed));
+    let itunes = episode.itunes_ext.clone().unwrap_or_default();
+
+    EpisodeRecord
+    {
+        index,
+        title: episode_title(episode),
+        description: episode_description(episode),
+        author: episode_author(episode, feed),
+        published: episode.pub_date.as_deref().and_then(parse_pub_date).map(|date| date.to_rfc3339()),
+        pub_date: episode.pub_date.clone(),
+        guid: episode.guid.as_ref().map(|guid| guid.value.clone()),
+        link: episode.link.clone(),
+        downloadable: episode_enclosure(episode).is_some(),
+        enclosure: episode.enclosure.as_ref().map(|enclosure| EnclosureRecord
+        {
+            url: enclosure.url.clone(),
+            length: enclosure.length.parse().ok().filter(|length| *length > 0),
+            mime_type: enclosure.mime_type.clone()
+        }),
+        duration_seconds: itunes.duration.as_deref().and_then(parse_duration).map(|duration| duration.as_secs()),
+        explicit: episode_explicit(episode, feed),
+        season: namespace.season.as_ref().map(|season| season.number.clone()).or(itunes.season),
+        season_name: namespace.season.and_then(|season| season.name),
+        episode: namespace.episode.as_ref().map(|number| number.number.clone()).or(itunes.episode),
+        episode_display: namespace.episode.and_then(|number| number.display),
+        categories: episode.categories.iter().map(|category| category.name.clone()).collect(),
+        chapters: namespace.chapters,
+        transcripts: namespace.transcripts,
+        persons: namespace.persons,
+        funding: namespace.funding
+    }
+}
+
+fn credentials_record(credentials: &FeedCredentials, show_secrets: bool) -> Option<CredentialsRecord>
+{
+    if credentials.is_empty() { return None }
+
+    let secret_record = |secret: &Secret| match secret
+    {
+        Secret::Plain(value) => SecretRecord::Plain { value: Some(value.clone()).filter(|_| show_secrets) },
+        Secret::Stored(name) => SecretRecord::Stored { name: name.clone() }
+    };
+
+    let auth = match &credentials.auth
+    {
+        FeedAuth::None => AuthRecord::None,
+        FeedAuth::Basic { username, password } => AuthRecord::Basic { username: username.clone(), password: secret_record(password) },
+        FeedAuth::Bearer { token } => AuthRecord::Bearer { token: secret_record(token) }
+    };
+
+    let headers = credentials.headers.iter()
+        .map(|(name, value)| HeaderRecord { name: name.clone(), value: secret_record(value) })
+        .collect();
+
+    Some(CredentialsRecord { auth, headers })
+}
+
+// Times that were never set, like the cache time of a podcast that was never fetched, are left out.
+fn format_time(time: SystemTime) -> Option<String>
+{
+    if time == UNIX_EPOCH { return None }
+    Some(DateTime::<Utc>::from(time).to_rfc3339())
+}
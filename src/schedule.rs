@@ -0,0 +1,150 @@
+// Scheduled syncing without a long-running process, for 'podclaw schedule'. Either a systemd user timer or a crontab
+// line runs 'podclaw update --all' every so often.
+
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::Serialize;
+use crate::error::{Error, Result};
+
+pub const SERVICE_NAME: &str = "podclaw-update.service";
+pub const TIMER_NAME: &str = "podclaw-update.timer";
+
+// Ends the crontab line Podclaw manages, so it can be found again without touching the user's other lines.
+pub const CRON_MARKER: &str = "# podclaw schedule";
+
+// The timer spreads runs out by up to this share of the cadence, and never by more than 'MAX_RANDOM_DELAY'.
+const RANDOM_DELAY_DIVISOR: u32 = 20;
+const MAX_RANDOM_DELAY: Duration = Duration::from_secs(10 * 60);
+
+// What the scheduled job runs: this executable, with the same config folder when 'PODCLAW_CONFIG_DIR' moved it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduleSpec
+{
+    pub every: Duration,
+    pub executable: PathBuf,
+    pub config_dir: Option<PathBuf>
+}
+
+// What's installed. The timer's state is missing when 'systemctl' couldn't be asked.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ScheduleStatus
+{
+    pub systemd_installed: bool,
+    pub systemd_every_seconds: Option<u64>,
+    pub timer_active: Option<bool>,
+    pub unit_dir: PathBuf,
+    pub cron_line: Option<String>
+}
+
+impl ScheduleSpec
+{
+    // Anything shorter than a minute would have the job running back to back, and cron can't do it anyway.
+    pub fn new(every: Duration, executable: PathBuf, config_dir: Option<PathBuf>) -> Result<Self>
+    {
+        if every < Duration::from_secs(60) { return Err(Error::Invalid(String::from("Scheduled updates have to be at least a minute apart."))) }
+        Ok(ScheduleSpec { every, executable, config_dir })
+    }
+
+    pub fn systemd_service(&self) -> String
+    {
+        let environment = self.config_dir.as_ref()
+            .map(|config_dir| format!("Environment={}\n", systemd_quote(&format!("PODCLAW_CONFIG_DIR={}", config_dir.display()))))
+            .unwrap_or_default();
+
+        format!("[Unit]\nDescription=Update Podclaw's podcasts\nWants=network-online.target\nAfter=network-online.target\n\n\
+            [Service]\nType=oneshot\n{}ExecStart={} update --all\n",
+            environment, systemd_quote(&self.executable.display().to_string()))
+    }
+
+    pub fn systemd_timer(&self) -> String
+    {
+        let random_delay = (self.every / RANDOM_DELAY_DIVISOR).min(MAX_RANDOM_DELAY);
+
+        format!("[Unit]\nDescription=Update Podclaw's podcasts every {}\n\n\
+            [Timer]\nOnBootSec=5min\nOnUnitActiveSec={}s\nRandomizedDelaySec={}s\n\n\
+            [Install]\nWantedBy=timers.target\n",
+            describe_every(self.every), self.every.as_secs(), random_delay.as_secs())
+    }
+
+    // Cron only repeats evenly within an hour or a day, so the cadence has to divide one of them.
+    pub fn cron_line(&self) -> Result<String>
+    {
+        let minutes = self.every.as_secs() / 60;
+        let hours = minutes / 60;
+        let timing = match (self.every.as_secs() % 60, minutes % 60)
+        {
+            (0, _) if minutes == 1 => String::from("* * * * *"),
+            (0, _) if minutes < 60 && 60 % minutes == 0 => format!("*/{} * * * *", minutes),
+            (0, 0) if hours == 1 => String::from("0 * * * *"),
+            (0, 0) if hours < 24 && 24 % hours == 0 => format!("0 */{} * * *", hours),
+            (0, 0) if hours == 24 => String::from("0 0 * * *"),
+            _ => return Err(Error::Invalid(format!("Cron can't run something every {}. Use minutes that divide an hour, hours that divide a day, or a systemd timer.", describe_every(self.every))))
+        };
+
+        let environment = self.config_dir.as_ref()
+            .map(|config_dir| format!("PODCLAW_CONFIG_DIR={} ", cron_quote(&config_dir.display().to_string())))
+            .unwrap_or_default();
+
+        Ok(format!("{} {}{} update --all {}", timing, environment, cron_quote(&self.executable.display().to_string()), CRON_MARKER))
+    }
+}
+
+// Where systemd looks for the user's own units, which needs no root to write to.
+pub fn systemd_unit_dir() -> Result<PathBuf>
+{
+    dirs::config_dir()
+        .map(|config_dir| config_dir.join("systemd").join("user"))
+        .ok_or_else(|| Error::Storage(String::from("Couldn't find the user's config folder for the systemd units.")))
+}
+
+// The cadence of the installed timer, read back from its 'OnUnitActiveSec'.
+pub fn installed_timer_every(unit_dir: &Path) -> Option<Duration>
+{
+    let timer = read_to_string(unit_dir.join(TIMER_NAME)).ok()?;
+    timer.lines()
+        .find_map(|line| line.strip_prefix("OnUnitActiveSec="))
+        .and_then(|seconds| seconds.trim_end_matches('s').parse().ok())
+        .map(Duration::from_secs)
+}
+
+// Replaces Podclaw's line in a crontab, or adds it at the end. Every other line is kept as it was.
+pub fn with_cron_line(crontab: &str, line: &str) -> String
+{
+    let mut lines: Vec<&str> = crontab.lines().filter(|existing| !existing.ends_with(CRON_MARKER)).collect();
+    lines.push(line);
+    lines.join("\n") + "\n"
+}
+
+pub fn without_cron_line(crontab: &str) -> String
+{
+    let lines: Vec<&str> = crontab.lines().filter(|existing| !existing.ends_with(CRON_MARKER)).collect();
+    if lines.is_empty() { String::new() } else { lines.join("\n") + "\n" }
+}
+
+pub fn find_cron_line(crontab: &str) -> Option<&str>
+{
+    crontab.lines().find(|line| line.ends_with(CRON_MARKER))
+}
+
+// Like '6h' or '90m', whichever unit divides the cadence evenly.
+pub fn describe_every(every: Duration) -> String
+{
+    let seconds = every.as_secs();
+    if seconds.is_multiple_of(3600) { format!("{}h", seconds / 3600) }
+    else if seconds.is_multiple_of(60) { format!("{}m", seconds / 60) }
+    else { format!("{}s", seconds) }
+}
+
+
+// systemd treats '%' as the start of a specifier, and splits arguments on spaces unless they're quoted.
+fn systemd_quote(value: &str) -> String
+{
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('%', "%%"))
+}
+
+// Cron runs its lines through the shell, and a bare '%' ends the command there.
+fn cron_quote(value: &str) -> String
+{
+    format!("'{}'", value.replace('\'', "'\\''").replace('%', "\\%"))
+}
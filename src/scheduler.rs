@@ -0,0 +1,128 @@
+// Decides when 'podclaw daemon' refreshes each podcast. A podcast is due once its update interval has passed since its
+// cache was last updated, plus a little jitter so podcasts added together don't all hit the network at once.
+
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use crate::manage_feeds::{is_cache_outdated, next_update};
+use crate::structs::Podcast;
+
+// Jitter is at most this share of a podcast's interval, and never more than 'MAX_JITTER'.
+const JITTER_DIVISOR: u32 = 20;
+const MAX_JITTER: Duration = Duration::from_secs(10 * 60);
+
+// Even a podcast with an interval of zero isn't refreshed more often than this.
+pub const MIN_REFRESH_GAP: Duration = Duration::from_secs(60);
+
+// How long to wait before trying again after a refresh fails, unless the podcast's interval is shorter.
+pub const RETRY_DELAY: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Default)]
+pub struct Scheduler
+{
+    entries: HashMap<String, ScheduleEntry>
+}
+
+struct ScheduleEntry
+{
+    cache_time: SystemTime,
+    due: SystemTime
+}
+
+// Notices when a file is changed by something else, like another Podclaw command editing the storage.
+pub struct FileWatcher
+{
+    path: PathBuf,
+    modified: Option<SystemTime>
+}
+
+impl Scheduler
+{
+    // Plans every podcast that's new or whose cache was updated since it was last planned, and forgets podcasts that
+    // were removed or locked. New podcasts that are already outdated are due right away.
+    pub fn sync(&mut self, podcasts: &[Podcast], now: SystemTime)
+    {
+        self.entries.retain(|alias, _| podcasts.iter().any(|podcast| podcast.alias == *alias && !podcast.is_locked));
+
+        for podcast in podcasts
+        {
+            let Some(next_update) = next_update(podcast) else { continue };
+            let planned = match self.entries.get(&podcast.alias)
+            {
+                Some(entry) if entry.cache_time == podcast.cache_time => continue,
+                Some(_) => next_update.max(podcast.cache_time + MIN_REFRESH_GAP),
+                None if is_cache_outdated(podcast) => now,
+                None => next_update
+            };
+
+            let due = planned + jitter(podcast.update_interval);
+            self.entries.insert(podcast.alias.clone(), ScheduleEntry { cache_time: podcast.cache_time, due });
+        }
+    }
+
+    // The indices of the podcasts that should be refreshed now, the longest overdue first.
+    pub fn due(&self, podcasts: &[Podcast], now: SystemTime) -> Vec<usize>
+    {
+        let mut due: Vec<(SystemTime, usize)> = podcasts.iter().enumerate()
+            .filter_map(|(index, podcast)| self.entries.get(&podcast.alias).map(|entry| (entry.due, index)))
+            .filter(|(due, _)| *due <= now)
+            .collect();
+
+        due.sort();
+        due.into_iter().map(|(_, index)| index).collect()
+    }
+
+    // Puts off a podcast whose refresh failed, so a broken feed isn't retried over and over.
+    pub fn postpone(&mut self, podcast: &Podcast, now: SystemTime)
+    {
+        let delay = podcast.update_interval.clamp(MIN_REFRESH_GAP, RETRY_DELAY);
+        if let Some(entry) = self.entries.get_mut(&podcast.alias) { entry.due = now + delay + jitter(delay) }
+    }
+
+    pub fn next_due(&self) -> Option<SystemTime>
+    {
+        self.entries.values().map(|entry| entry.due).min()
+    }
+
+    pub fn due_at(&self, alias: &str) -> Option<SystemTime>
+    {
+        self.entries.get(alias).map(|entry| entry.due)
+    }
+}
+
+impl FileWatcher
+{
+    pub fn new(path: PathBuf) -> Self
+    {
+        let modified = modified_time(&path);
+        FileWatcher { path, modified }
+    }
+
+    // Whether the file was changed, created or removed since the last check.
+    pub fn has_changed(&mut self) -> bool
+    {
+        let modified = modified_time(&self.path);
+        if modified == self.modified { return false }
+
+        self.modified = modified;
+        true
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime>
+{
+    path.metadata().and_then(|metadata| metadata.modified()).ok()
+}
+
+// A random delay between zero and a twentieth of the interval. The standard library's hasher is randomly seeded, which
+// is random enough for spreading requests out.
+fn jitter(interval: Duration) -> Duration
+{
+    let max_jitter = (interval / JITTER_DIVISOR).min(MAX_JITTER);
+    if max_jitter.is_zero() { return Duration::ZERO }
+
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_millis(random % max_jitter.as_millis() as u64)
+}
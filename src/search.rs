@@ -0,0 +1,165 @@
+// Searches a podcast directory for feeds. Two APIs are supported: the iTunes Search API, which needs no account, and the
+// Podcast Index API. Either can point at another base URL, like a self-hosted mirror.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use crate::error::{Error, Result};
+use crate::http::{HttpClient, HttpRequest};
+
+const ITUNES_BASE_URL: &str = "https://itunes.apple.com";
+const PODCAST_INDEX_BASE_URL: &str = "https://api.podcastindex.org/api/1.0";
+const DEFAULT_LIMIT: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchProvider
+{
+    #[default]
+    Itunes,
+    PodcastIndex
+}
+
+// The '[search]' config section. Podcast Index needs an API key, and the name of a stored secret holding the API secret.
+// Mirrors that don't check them can leave both out.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchSettings
+{
+    pub provider: SearchProvider,
+    pub base_url: Option<String>,
+    pub limit: Option<usize>,
+    pub api_key: Option<String>,
+    pub api_secret_name: Option<String>
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SearchResult
+{
+    pub title: String,
+    pub author: Option<String>,
+    pub feed_url: String
+}
+
+impl SearchSettings
+{
+    pub fn base_url(&self) -> &str
+    {
+        let default_url = match self.provider
+        {
+            SearchProvider::Itunes => ITUNES_BASE_URL,
+            SearchProvider::PodcastIndex => PODCAST_INDEX_BASE_URL
+        };
+
+        self.base_url.as_deref().unwrap_or(default_url).trim_end_matches('/')
+    }
+
+    pub fn limit(&self) -> usize
+    {
+        self.limit.unwrap_or(DEFAULT_LIMIT)
+    }
+}
+
+
+// Searches for podcasts matching the terms. Results without a feed URL are skipped, since they can't be added.
+pub fn search_podcasts(client: &dyn HttpClient, settings: &SearchSettings, api_secret: Option<&str>, terms: &str) -> Result<Vec<SearchResult>>
+{
+    if terms.trim().is_empty() { return Err(Error::Invalid(String::from("No search terms given."))) }
+
+    let limit = settings.limit().to_string();
+    let request = match settings.provider
+    {
+        SearchProvider::Itunes => HttpRequest::new(&search_url(settings, "/search", &[("media", "podcast"), ("entity", "podcast"), ("term", terms), ("limit", &limit)])?),
+        SearchProvider::PodcastIndex =>
+        {
+            let mut request = HttpRequest::new(&search_url(settings, "/search/byterm", &[("q", terms), ("max", &limit)])?);
+            if let Some(api_key) = &settings.api_key { request.headers.extend(podcast_index_headers(api_key, api_secret.unwrap_or_default())) }
+            request
+        }
+    };
+
+    let response = client.get(&request)?;
+    if !response.is_success()
+    {
+        return Err(Error::Network(format!("Searching '{}' failed: the server answered with HTTP {}.", settings.base_url(), response.status)));
+    }
+
+    let parse_error = |error: serde_json::Error| Error::Parse(format!("Failed to parse the search results: {}", error));
+    let results = match settings.provider
+    {
+        SearchProvider::Itunes =>
+        {
+            let answer: ItunesAnswer = serde_json::from_slice(&response.body).map_err(parse_error)?;
+            answer.results.into_iter()
+                .filter_map(|result| Some(SearchResult { title: result.collection_name?, author: result.artist_name, feed_url: result.feed_url? }))
+                .collect()
+        }
+
+        SearchProvider::PodcastIndex =>
+        {
+            let answer: PodcastIndexAnswer = serde_json::from_slice(&response.body).map_err(parse_error)?;
+            answer.feeds.into_iter()
+                .filter_map(|feed| Some(SearchResult { title: feed.title?, author: feed.author, feed_url: feed.url? }))
+                .collect()
+        }
+    };
+
+    Ok(results)
+}
+
+fn search_url(settings: &SearchSettings, path: &str, parameters: &[(&str, &str)]) -> Result<String>
+{
+    let base_url = settings.base_url();
+
+    reqwest::Url::parse_with_params(&format!("{}{}", base_url, path), parameters)
+        .map(|url| url.to_string())
+        .map_err(|_| Error::Invalid(format!("Invalid search base URL '{}'.", base_url)))
+}
+
+// Podcast Index signs each request with a SHA-1 hash of the key, the secret, and the current time.
+fn podcast_index_headers(api_key: &str, api_secret: &str) -> Vec<(String, String)>
+{
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs().to_string();
+    let signature: String = Sha1::digest(format!("{}{}{}", api_key, api_secret, now))
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    vec![
+        (String::from("X-Auth-Key"), api_key.to_string()),
+        (String::from("X-Auth-Date"), now),
+        (String::from("Authorization"), signature)
+    ]
+}
+
+
+#[derive(Deserialize)]
+struct ItunesAnswer
+{
+    #[serde(default)]
+    results: Vec<ItunesResult>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItunesResult
+{
+    collection_name: Option<String>,
+    artist_name: Option<String>,
+    feed_url: Option<String>
+}
+
+#[derive(Deserialize)]
+struct PodcastIndexAnswer
+{
+    #[serde(default)]
+    feeds: Vec<PodcastIndexFeed>
+}
+
+#[derive(Deserialize)]
+struct PodcastIndexFeed
+{
+    title: Option<String>,
+    author: Option<String>,
+    url: Option<String>
+}
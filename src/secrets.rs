@@ -0,0 +1,145 @@
+// An encrypted file for feed passwords, tokens, and other secrets, kept next to the storage. Podcasts refer to secrets by
+// name, so the storage itself never holds them and the config folder can be synced without leaking anything.
+//
+// The file is the magic bytes, a format version, a random salt and nonce, and then the encrypted secrets. The key is
+// derived from a passphrase with Argon2id, and the secrets are sealed with ChaCha20-Poly1305.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use crate::error::{Error, Result};
+use crate::utils::get_config_dir;
+
+const SECRETS_MAGIC: &[u8; 4] = b"PCLS";
+const SECRETS_VERSION: u32 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+
+// The passphrase can come from here instead of a prompt, for scripts and scheduled runs.
+pub const PASSPHRASE_VARIABLE: &str = "PODCLAW_PASSPHRASE";
+
+#[derive(Default)]
+pub struct SecretStore
+{
+    secrets: BTreeMap<String, String>
+}
+
+impl SecretStore
+{
+    pub fn new() -> Self
+    {
+        SecretStore::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str>
+    {
+        self.secrets.get(name).map(|value| value.as_str())
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()>
+    {
+        if name.is_empty() || name.contains(char::is_whitespace)
+        {
+            return Err(Error::Invalid(format!("Invalid secret name '{}'. Names can't be empty or contain spaces.", name)));
+        }
+
+        self.secrets.insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    // Returns whether the secret existed.
+    pub fn remove(&mut self, name: &str) -> bool
+    {
+        self.secrets.remove(name).is_some()
+    }
+
+    // Names only, in alphabetical order. The values stay in the store.
+    pub fn names(&self) -> Vec<&str>
+    {
+        self.secrets.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+
+pub fn get_secrets_path() -> Result<PathBuf>
+{
+    Ok(get_config_dir()?.join(Path::new("podclaw_secrets.bin")))
+}
+
+// A missing file is an empty store, so the first secret can be saved with any passphrase.
+pub fn open_secrets_at(path: &Path, passphrase: &str) -> Result<SecretStore>
+{
+    if !path.try_exists()? { return Ok(SecretStore::new()) }
+
+    let mut encrypted_data: Vec<u8> = Vec::new();
+    File::open(path)?.read_to_end(&mut encrypted_data)?;
+
+    decrypt_secrets(&encrypted_data, passphrase)
+}
+
+pub fn save_secrets_at(store: &SecretStore, path: &Path, passphrase: &str) -> Result<()>
+{
+    let encrypted_data = encrypt_secrets(store, passphrase)?;
+
+    File::create(path)?.write_all(&encrypted_data)?;
+    Ok(())
+}
+
+// Every save uses a fresh salt and nonce, so a nonce is never reused with the same key.
+pub fn encrypt_secrets(store: &SecretStore, passphrase: &str) -> Result<Vec<u8>>
+{
+    let mut salt = [0u8; SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let plain_data = bincode::serialize(&store.secrets)?;
+    let sealed_data = cipher.encrypt(&nonce, plain_data.as_slice())
+        .map_err(|_| Error::Storage(String::from("Failed to encrypt the secrets file.")))?;
+
+    let mut encoded_payload: Vec<u8> = SECRETS_MAGIC.to_vec();
+    encoded_payload.extend(bincode::serialize(&SECRETS_VERSION)?);
+    encoded_payload.extend(salt);
+    encoded_payload.extend(nonce);
+    encoded_payload.extend(sealed_data);
+
+    Ok(encoded_payload)
+}
+
+pub fn decrypt_secrets(encrypted_data: &[u8], passphrase: &str) -> Result<SecretStore>
+{
+    let damaged = || Error::Storage(String::from("The secrets file is damaged or isn't a Podclaw secrets file."));
+
+    let versioned_data = encrypted_data.strip_prefix(SECRETS_MAGIC).ok_or_else(damaged)?;
+    if versioned_data.len() < 4 + SALT_LENGTH + NONCE_LENGTH { return Err(damaged()) }
+
+    let version = bincode::deserialize::<u32>(&versioned_data[..4])?;
+    if version > SECRETS_VERSION
+    {
+        return Err(Error::Storage(format!("The secrets file was written by a newer version of Podclaw (format {}).", version)));
+    }
+
+    let (salt, rest) = versioned_data[4..].split_at(SALT_LENGTH);
+    let (nonce, sealed_data) = rest.split_at(NONCE_LENGTH);
+
+    // A wrong passphrase and a tampered file look the same here, since both fail authentication.
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, salt)?);
+    let plain_data = cipher.decrypt(Nonce::from_slice(nonce), sealed_data)
+        .map_err(|_| Error::Invalid(String::from("Couldn't unlock the secrets file. The passphrase is wrong, or the file was changed.")))?;
+
+    Ok(SecretStore { secrets: bincode::deserialize(&plain_data)? })
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key>
+{
+    let mut key = Key::default();
+    Argon2::default().hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|error| Error::Invalid(format!("Failed to derive a key from the passphrase: {}", error)))?;
+
+    Ok(key)
+}
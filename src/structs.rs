@@ -1,10 +1,59 @@
 use std::path::PathBuf;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use serde::{Serialize, Deserialize};
+use crate::auth::FeedCredentials;
+use crate::auto_download::AutoDownload;
+use crate::http::NetworkOverrides;
 
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Podcast
+{
+    pub alias: String,
+    pub feed_url: String,
+    pub download_path: PathBuf,
+    pub update_interval: Duration,
+    pub cache_time: SystemTime,
+    pub cache_content: String,
+    pub is_locked: bool,
+    pub network: NetworkOverrides,
+    pub credentials: FeedCredentials,
+    pub previous_feed_urls: Vec<PreviousFeedUrl>,
+    pub tags: Vec<String>,
+
+    // The 'feed_fields::episode_id' of each episode marked as played.
+    pub played_episodes: Vec<String>,
+
+    // Which new episodes 'update' and 'daemon' download on their own.
+    pub auto_download: AutoDownload
+}
+
+// Why a podcast's feed URL was changed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum FeedMoveReason
+{
+    // The server answered with a permanent redirect, a 301 or 308.
+    Redirect,
+
+    // The feed itself pointed somewhere else with 'itunes:new-feed-url'.
+    NewFeedUrl,
+
+    // The user changed it with 'edit'.
+    Edit
+}
+
+// A feed URL that a podcast used before, and when and why it was replaced.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PreviousFeedUrl
+{
+    pub url: String,
+    pub replaced_at: SystemTime,
+    pub reason: FeedMoveReason
+}
+
+// How podcasts were stored by Podclaw 1.0.1. Only used to read old storage files.
+#[derive(Deserialize)]
+pub struct LegacyPodcast
 {
     pub alias: String,
     pub feed_url: String,
@@ -27,19 +76,62 @@ impl Podcast
             update_interval: Duration::default(),
             cache_time: UNIX_EPOCH,
             cache_content: String::new(),
-            is_locked: false
+            is_locked: false,
+            network: NetworkOverrides::default(),
+            credentials: FeedCredentials::default(),
+            previous_feed_urls: Vec::new(),
+            tags: Vec::new(),
+            played_episodes: Vec::new(),
+            auto_download: AutoDownload::default()
+        }
+    }
+}
+
+impl From<LegacyPodcast> for Podcast
+{
+    fn from(legacy: LegacyPodcast) -> Self
+    {
+        Podcast
+        {
+            alias: legacy.alias,
+            feed_url: legacy.feed_url,
+            download_path: legacy.download_path,
+            update_interval: legacy.update_interval,
+            cache_time: legacy.cache_time,
+            cache_content: legacy.cache_content,
+            is_locked: legacy.is_locked,
+            ..Podcast::new()
+        }
+    }
+}
+
+impl Default for Podcast
+{
+    fn default() -> Self { Podcast::new() }
+}
+
+impl FeedMoveReason
+{
+    pub fn description(&self) -> &'static str
+    {
+        match self
+        {
+            FeedMoveReason::Redirect => "permanent redirect",
+            FeedMoveReason::NewFeedUrl => "new feed URL in the feed",
+            FeedMoveReason::Edit => "edited"
         }
     }
 }
 
-#[derive(Clone)]
+// The markers that start each line of output, built from a theme by 'theme::build_text_deco'.
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct TextDeco
 {
-    pub completion: &'static str,
-    pub general: &'static str,
-    pub important: &'static str,
-    pub error: &'static str,
-    pub prompt: &'static str,
-    pub input: &'static str,
-    pub verbose: &'static str
+    pub completion: String,
+    pub general: String,
+    pub important: String,
+    pub error: String,
+    pub prompt: String,
+    pub input: String,
+    pub verbose: String
 }
\ No newline at end of file
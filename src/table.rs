@@ -0,0 +1,353 @@
+// The aligned tables 'list' and 'find' print. Columns can be picked and sorted by, and long tables go through a pager when
+// they're shown in a terminal.
+
+use std::cmp::Ordering;
+use std::io::{stdout, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+use chrono::{DateTime, FixedOffset, Local};
+use clap::Args as ClapArgs;
+use rss::Item;
+use podclaw::{Error, Result};
+use podclaw::structs::Podcast;
+use podclaw::feed_fields::*;
+use podclaw::find::FoundEpisode;
+use podclaw::manage_feeds::*;
+use podclaw::utils::*;
+use crate::display::TXTD;
+
+// Titles are cut off after this many characters, so one long title doesn't push the other columns off the screen.
+const MAX_TITLE_WIDTH: usize = 48;
+
+// Shown in a cell without a value. Tables use this instead of 'feed_fields::MISSING', which is too wide for a column.
+const EMPTY_CELL: &str = "-";
+
+const DEFAULT_PAGER: &str = "less -FRX";
+
+#[derive(ClapArgs, Default)]
+pub struct TableOptions
+{
+    /// The columns to show, separated by commas. Podcasts have index, alias, title, episodes, unplayed, updated,
+    /// next-update, locked and tags. Episodes have index, title, date, duration, size, downloaded and played.
+    #[arg(required = false, long = "columns", value_delimiter = ',')]
+    columns: Vec<String>,
+
+    /// Sorts by a column, like 'title'. A '-' in front, like '-date', sorts from the largest value down.
+    #[arg(required = false, long = "sort", allow_hyphen_values = true, value_name = "COLUMN")]
+    sort: Option<String>,
+
+    /// Shows only this many rows, after sorting.
+    #[arg(required = false, short = 'n', long = "limit")]
+    limit: Option<usize>,
+
+    /// Prints the table right away, even in a terminal, instead of going through '$PAGER'.
+    #[arg(required = false, long = "no-pager")]
+    no_pager: bool
+}
+
+// A column's name for '--columns' and '--sort', and its header. Numbers line up on the right.
+struct Column
+{
+    name: &'static str,
+    header: &'static str,
+    is_numeric: bool
+}
+
+const fn column(name: &'static str, header: &'static str, is_numeric: bool) -> Column
+{
+    Column { name, header, is_numeric }
+}
+
+const PODCAST_COLUMNS: [Column; 9] =
+[
+    column("index", "#", true), column("alias", "Alias", false), column("title", "Title", false),
+    column("episodes", "Episodes", true), column("unplayed", "Unplayed", true), column("updated", "Updated", false),
+    column("next-update", "Next Update", false), column("locked", "Locked", false), column("tags", "Tags", false)
+];
+
+const EPISODE_COLUMNS: [Column; 7] =
+[
+    column("index", "#", true), column("title", "Title", false), column("date", "Date", false),
+    column("duration", "Duration", true), column("size", "Size", true), column("downloaded", "Downloaded", false),
+    column("played", "Played", false)
+];
+
+const FOUND_COLUMNS: [Column; 6] =
+[
+    column("rank", "#", true), column("alias", "Alias", false), column("episode", "Episode", true),
+    column("date", "Date", false), column("title", "Title", false), column("id", "Id", false)
+];
+
+// What a cell is sorted by, so dates, durations and sizes sort by value instead of by their text.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey
+{
+    Number(i64),
+    Text(String),
+
+    // Sorted last in both directions.
+    Missing
+}
+
+struct Cell
+{
+    text: String,
+    key: SortKey
+}
+
+impl Cell
+{
+    fn text(text: String) -> Self
+    {
+        Cell { key: SortKey::Text(text.to_lowercase()), text }
+    }
+
+    fn number(number: usize) -> Self
+    {
+        Cell { text: number.to_string(), key: SortKey::Number(number as i64) }
+    }
+
+    fn flag(flag: bool) -> Self
+    {
+        Cell { text: String::from(if flag { "yes" } else { "no" }), key: SortKey::Number(flag as i64) }
+    }
+
+    fn missing() -> Self
+    {
+        Cell { text: String::from(EMPTY_CELL), key: SortKey::Missing }
+    }
+}
+
+// Rows of cells for every one of 'columns', so rows can be sorted by a column that isn't shown.
+pub struct Table
+{
+    columns: &'static [Column],
+    rows: Vec<Vec<Cell>>
+}
+
+pub fn podcast_table(podcasts: &[Podcast], listed: &[usize]) -> Table
+{
+    let rows = listed.iter().copied().map(|podcast_index|
+    {
+        let podcast = &podcasts[podcast_index];
+        let feed = load_cached_feed(podcast).ok();
+
+        let next_update = match next_update(podcast)
+        {
+            None => Cell { text: String::from("never"), key: SortKey::Missing },
+            Some(_) if is_cache_outdated(podcast) => Cell { text: String::from("due"), key: SortKey::Number(0) },
+            Some(next_update) => time_cell(next_update)
+        };
+
+        vec![
+            Cell::number(podcast_index),
+            Cell::text(podcast.alias.clone()),
+            feed.as_ref().and_then(channel_title).map_or_else(Cell::missing, |title| Cell::text(shorten(&title))),
+            feed.as_ref().map_or_else(Cell::missing, |feed| Cell::number(feed.items.len())),
+            feed.as_ref().map_or_else(Cell::missing, |feed| Cell::number(unplayed_count(podcast, feed))),
+            if podcast.cache_time == UNIX_EPOCH { Cell { text: String::from("never"), key: SortKey::Missing } } else { time_cell(podcast.cache_time) },
+            next_update,
+            Cell::flag(podcast.is_locked),
+            if podcast.tags.is_empty() { Cell::missing() } else { Cell::text(podcast.tags.join(", ")) }
+        ]
+    }).collect();
+
+    Table { columns: &PODCAST_COLUMNS, rows }
+}
+
+// Only the 'shown' episodes get rows, but they keep their indices. Episodes count as downloaded when their audio is
+// where 'get' would put it, which is named by the episode's index in 'file_indices', not the one shown.
+pub fn episode_table(podcast: &Podcast, episodes: &[Item], file_indices: &[usize], shown: &[usize], template: &str) -> Table
+{
+    let rows = shown.iter().map(|episode_index| (*episode_index, &episodes[*episode_index])).map(|(episode_index, episode)|
+    {
+        let date = episode.pub_date.as_deref().and_then(parse_pub_date);
+        let duration = episode.itunes_ext.as_ref().and_then(|itunes| itunes.duration.as_deref()).and_then(parse_duration);
+        let size = episode_enclosure(episode).and_then(|enclosure| enclosure.length.parse::<u64>().ok()).filter(|length| *length > 0);
+        let downloaded = episode_enclosure(episode)
+            .map(|_| episode_download_path(podcast, file_indices[episode_index], episode, template).exists());
+
+        vec![
+            Cell::number(episode_index),
+            episode_title(episode).map_or_else(Cell::missing, |title| Cell::text(shorten(&title))),
+            date.map_or_else(Cell::missing, date_cell),
+            duration.map_or_else(Cell::missing, |duration| Cell { text: format_duration(duration), key: SortKey::Number(duration.as_secs() as i64) }),
+            size.map_or_else(Cell::missing, |size| Cell { text: format_bytes(size), key: SortKey::Number(size as i64) }),
+            downloaded.map_or_else(Cell::missing, Cell::flag),
+            Cell::flag(is_played(podcast, episode))
+        ]
+    }).collect();
+
+    Table { columns: &EPISODE_COLUMNS, rows }
+}
+
+pub fn found_table(found: &[FoundEpisode]) -> Table
+{
+    let rows = found.iter().enumerate().map(|(rank, episode)|
+    {
+        vec![
+            Cell::number(rank + 1),
+            Cell::text(episode.alias.clone()),
+            Cell::number(episode.index),
+            episode.date.map_or_else(Cell::missing, date_cell),
+            episode.title.as_ref().map_or_else(Cell::missing, |title| Cell::text(shorten(title))),
+            Cell::text(episode.id.clone())
+        ]
+    }).collect();
+
+    Table { columns: &FOUND_COLUMNS, rows }
+}
+
+impl Table
+{
+    // Picks, sorts and cuts down the rows like the options say, and lines up what's left.
+    pub fn render(mut self, options: &TableOptions) -> Result<String>
+    {
+        let shown: Vec<usize> = if options.columns.is_empty() { (0..self.columns.len()).collect() }
+        else
+        {
+            options.columns.iter().map(|name| self.column_position(name)).collect::<Result<Vec<usize>>>()?
+        };
+
+        if let Some(sort) = &options.sort
+        {
+            let (name, is_descending) = match sort.strip_prefix('-')
+            {
+                Some(name) => (name, true),
+                None => (sort.as_str(), false)
+            };
+            let position = self.column_position(name)?;
+
+            self.rows.sort_by(|first, second|
+            {
+                match (&first[position].key, &second[position].key)
+                {
+                    (SortKey::Missing, SortKey::Missing) => Ordering::Equal,
+                    (SortKey::Missing, _) => Ordering::Greater,
+                    (_, SortKey::Missing) => Ordering::Less,
+                    (first_key, second_key) if is_descending => second_key.cmp(first_key),
+                    (first_key, second_key) => first_key.cmp(second_key)
+                }
+            });
+        }
+
+        if let Some(limit) = options.limit { self.rows.truncate(limit) }
+
+        let widths: Vec<usize> = shown.iter().map(|position|
+        {
+            self.rows.iter().map(|row| row[*position].text.chars().count())
+                .chain([self.columns[*position].header.chars().count()])
+                .max().unwrap_or(0)
+        }).collect();
+
+        let headers: Vec<String> = shown.iter().zip(&widths)
+            .map(|(position, width)| format!("{:<width$}", self.columns[*position].header, width = width))
+            .collect();
+        let mut lines = vec![make_bold!(headers.join("  ").trim_end())];
+
+        for row in &self.rows
+        {
+            let cells: Vec<String> = shown.iter().zip(&widths).map(|(position, width)|
+            {
+                let text = &row[*position].text;
+                if self.columns[*position].is_numeric { format!("{:>width$}", text, width = width) }
+                else { format!("{:<width$}", text, width = width) }
+            }).collect();
+
+            lines.push(cells.join("  ").trim_end().to_string());
+        }
+
+        Ok(lines.iter().map(|line| format!("  {}\n", line)).collect())
+    }
+
+    fn column_position(&self, name: &str) -> Result<usize>
+    {
+        let name = name.trim().to_lowercase();
+        self.columns.iter().position(|column| column.name == name).ok_or_else(||
+        {
+            let names: Vec<&str> = self.columns.iter().map(|column| column.name).collect();
+            Error::Invalid(format!("Unknown column '{}'. The columns are: {}.", name, names.join(", ")))
+        })
+    }
+}
+
+// Lists some of the podcasts, given by index. The indices shown are the podcasts' places in the whole list.
+pub fn render_podcast_list(podcasts: &[Podcast], listed: &[usize], options: &TableOptions) -> Result<String>
+{
+    let table = podcast_table(podcasts, listed).render(options)?;
+    Ok(format!("{} Listing all registered podcasts...\n{}", TXTD.general, table))
+}
+
+// Lists the podcasts in a table for each of their tags, so a podcast with several tags shows up more than once.
+pub fn render_podcast_groups(podcasts: &[Podcast], listed: &[usize], options: &TableOptions) -> Result<String>
+{
+    let mut text = format!("{} Listing all registered podcasts by tag...\n", TXTD.general);
+
+    let mut groups: Vec<(String, Vec<usize>)> = all_tags(podcasts).into_iter()
+        .map(|(tag, _)|
+        {
+            let members = listed.iter().copied().filter(|index| podcasts[*index].tags.contains(&tag)).collect();
+            (format!("{}:", tag), members)
+        })
+        .collect();
+    groups.push((String::from("(untagged):"), listed.iter().copied().filter(|index| podcasts[*index].tags.is_empty()).collect()));
+
+    for (heading, members) in groups.into_iter().filter(|(_, members)| !members.is_empty())
+    {
+        text.push_str(&format!("\n  {}\n", make_bold!(heading)));
+        text.push_str(&podcast_table(podcasts, &members).render(options)?);
+    }
+
+    Ok(text)
+}
+
+pub fn render_episode_list(podcast: &Podcast, episodes: &[Item], file_indices: &[usize], shown: &[usize], template: &str, options: &TableOptions) -> Result<String>
+{
+    let table = episode_table(podcast, episodes, file_indices, shown, template).render(options)?;
+    let heading = if shown.len() == episodes.len() { String::from("all episodes") } else { format!("{} of {} episodes", shown.len(), episodes.len()) };
+    Ok(format!("{} Listing {} in the requested podcast...\n{}", TXTD.general, heading, table))
+}
+
+pub fn render_found_episodes(found: &[FoundEpisode]) -> Result<String>
+{
+    let count = if found.len() == 1 { String::from("1 episode") } else { format!("{} episodes", found.len()) };
+    let table = found_table(found).render(&TableOptions::default())?;
+    Ok(format!("{} Found {}, best matches first...\n{}", TXTD.general, count, table))
+}
+
+// Shows text through '$PAGER' when writing to a terminal, like git does. 'less' quits right away when everything fits
+// on the screen. Without a pager, or if it can't be started, the text is just printed.
+pub fn page(text: &str, options: &TableOptions)
+{
+    let pager = std::env::var("PAGER").unwrap_or(String::from(DEFAULT_PAGER));
+    let mut pager_parts = pager.split_whitespace();
+
+    let program = match pager_parts.next()
+    {
+        Some(program) if !options.no_pager && stdout().is_terminal() && program != "cat" => program,
+        _ => return print!("{}", text)
+    };
+
+    let Ok(mut child) = Command::new(program).args(pager_parts).stdin(Stdio::piped()).spawn() else { return print!("{}", text) };
+
+    // The pager can be quit before everything is written, which isn't an error.
+    if let Some(mut pager_input) = child.stdin.take() { let _ = pager_input.write_all(text.as_bytes()); }
+    let _ = child.wait();
+}
+
+fn date_cell(date: DateTime<FixedOffset>) -> Cell
+{
+    Cell { text: date.with_timezone(&Local).format("%Y-%m-%d").to_string(), key: SortKey::Number(date.timestamp()) }
+}
+
+fn time_cell(time: SystemTime) -> Cell
+{
+    let time = DateTime::<Local>::from(time);
+    Cell { text: time.format("%Y-%m-%d %H:%M").to_string(), key: SortKey::Number(time.timestamp()) }
+}
+
+fn shorten(text: &str) -> String
+{
+    if text.chars().count() <= MAX_TITLE_WIDTH { return text.to_string() }
+    format!("{}…", text.chars().take(MAX_TITLE_WIDTH - 1).collect::<String>())
+}
@@ -0,0 +1,266 @@
+// Test doubles for Podclaw's network access. 'FakeClient' answers requests from memory, while 'TestServer' is a
+// real HTTP server on 127.0.0.1 for tests that need to go through the actual client or the command line.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{sleep, spawn, JoinHandle};
+use std::time::Duration;
+use crate::error::{Error, Result};
+use crate::http::{follow_redirects, HttpClient, HttpRequest, HttpResponse};
+
+// A canned response, shared by both fakes.
+#[derive(Clone, Debug)]
+pub struct FakeResponse
+{
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub pause: Option<Duration>
+}
+
+impl FakeResponse
+{
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self
+    {
+        FakeResponse { status: 200, headers: Vec::new(), body: body.into(), pause: None }
+    }
+
+    pub fn status(status: u16) -> Self
+    {
+        FakeResponse { status, headers: Vec::new(), body: Vec::new(), pause: None }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self
+    {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    // Makes 'TestServer' wait this long before sending each byte of the body, to act like a slow or stalled server.
+    pub fn with_pause(mut self, pause: Duration) -> Self
+    {
+        self.pause = Some(pause);
+        self
+    }
+}
+
+
+// Answers requests from a map of URLs. Unknown URLs fail like an unreachable host would. Redirects are followed like the
+// real client does, and every hop is recorded as a request.
+#[derive(Default)]
+pub struct FakeClient
+{
+    responses: Mutex<HashMap<String, FakeResponse>>,
+    requests: Mutex<Vec<HttpRequest>>
+}
+
+impl FakeClient
+{
+    pub fn new() -> Self
+    {
+        FakeClient::default()
+    }
+
+    pub fn with_response(self, url: &str, response: FakeResponse) -> Self
+    {
+        self.set_response(url, response);
+        self
+    }
+
+    pub fn set_response(&self, url: &str, response: FakeResponse)
+    {
+        self.responses.lock().unwrap().insert(url.to_string(), response);
+    }
+
+    pub fn remove_response(&self, url: &str)
+    {
+        self.responses.lock().unwrap().remove(url);
+    }
+
+    // Every request made so far, oldest first.
+    pub fn requests(&self) -> Vec<HttpRequest>
+    {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn get_once(&self, request: &HttpRequest) -> Result<HttpResponse>
+    {
+        self.requests.lock().unwrap().push(request.clone());
+
+        match self.responses.lock().unwrap().get(&request.url)
+        {
+            Some(response) => Ok(HttpResponse
+            {
+                status: response.status,
+                url: request.url.clone(),
+                headers: response.headers.clone(),
+                body: response.body.clone(),
+                redirects: Vec::new()
+            }),
+            None => Err(Error::Network(format!("Request to '{}' failed: no fake response registered", request.url)))
+        }
+    }
+}
+
+impl HttpClient for FakeClient
+{
+    fn get(&self, request: &HttpRequest) -> Result<HttpResponse>
+    {
+        follow_redirects(request, |hop| self.get_once(hop))
+    }
+}
+
+
+
+// A minimal HTTP/1.1 server that serves canned responses by path. It stops when dropped.
+// Each path can have a queue of responses, which are used up in order until only the last one is left.
+pub struct TestServer
+{
+    address: String,
+    routes: Arc<Mutex<HashMap<String, Vec<FakeResponse>>>>,
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+    is_stopping: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>
+}
+
+impl TestServer
+{
+    pub fn start() -> Self
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind the test server.");
+        let address = listener.local_addr().unwrap().to_string();
+
+        let routes: Arc<Mutex<HashMap<String, Vec<FakeResponse>>>> = Arc::default();
+        let requests: Arc<Mutex<Vec<HttpRequest>>> = Arc::default();
+        let is_stopping = Arc::new(AtomicBool::new(false));
+
+        let (thread_routes, thread_requests, thread_stopping) = (routes.clone(), requests.clone(), is_stopping.clone());
+        let handle = spawn(move ||
+        {
+            for stream in listener.incoming()
+            {
+                if thread_stopping.load(Ordering::SeqCst) { break }
+
+                if let Ok(stream) = stream
+                {
+                    let _ = serve_connection(stream, &thread_routes, &thread_requests);
+                }
+            }
+        });
+
+        TestServer { address, routes, requests, is_stopping, handle: Some(handle) }
+    }
+
+    // The full URL for a path on this server, like 'http://127.0.0.1:1234/feed.xml'.
+    pub fn url(&self, path: &str) -> String
+    {
+        format!("http://{}{}", self.address, path)
+    }
+
+    pub fn set_route(&self, path: &str, response: FakeResponse)
+    {
+        self.routes.lock().unwrap().insert(path.to_string(), vec![response]);
+    }
+
+    // Adds a response to the end of a path's queue.
+    pub fn queue_route(&self, path: &str, response: FakeResponse)
+    {
+        self.routes.lock().unwrap().entry(path.to_string()).or_default().push(response);
+    }
+
+    pub fn remove_route(&self, path: &str)
+    {
+        self.routes.lock().unwrap().remove(path);
+    }
+
+    pub fn requests(&self) -> Vec<HttpRequest>
+    {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for TestServer
+{
+    fn drop(&mut self)
+    {
+        self.is_stopping.store(true, Ordering::SeqCst);
+
+        // The server thread is blocked on 'accept', so it needs one last connection to notice it should stop.
+        let _ = TcpStream::connect(&self.address);
+        if let Some(handle) = self.handle.take() { let _ = handle.join(); }
+    }
+}
+
+fn serve_connection(stream: TcpStream, routes: &Mutex<HashMap<String, Vec<FakeResponse>>>, requests: &Mutex<Vec<HttpRequest>>) -> std::io::Result<()>
+{
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/").to_string();
+
+    let mut headers = Vec::new();
+    let mut content_length = 0;
+    loop
+    {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() { break }
+
+        if let Some((name, value)) = line.trim_end().split_once(':')
+        {
+            if name.eq_ignore_ascii_case("content-length") { content_length = value.trim().parse().unwrap_or(0) }
+            headers.push((name.to_string(), value.trim().to_string()));
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    requests.lock().unwrap().push(HttpRequest { url: path.clone(), headers, ..HttpRequest::default() });
+
+    let response = match routes.lock().unwrap().get_mut(&path)
+    {
+        Some(queue) if queue.len() > 1 => queue.remove(0),
+        Some(queue) => queue[0].clone(),
+        None => FakeResponse::status(404)
+    };
+
+    let mut writer = stream;
+    write!(writer, "HTTP/1.1 {} {}\r\n", response.status, reason_phrase(response.status))?;
+    for (name, value) in &response.headers { write!(writer, "{}: {}\r\n", name, value)? }
+    write!(writer, "Content-Length: {}\r\nConnection: close\r\n\r\n", response.body.len())?;
+    match response.pause
+    {
+        Some(pause) => for byte in &response.body
+        {
+            writer.flush()?;
+            sleep(pause);
+            writer.write_all(&[*byte])?;
+        }
+        None => writer.write_all(&response.body)?
+    }
+    writer.flush()
+}
+
+fn reason_phrase(status: u16) -> &'static str
+{
+    match status
+    {
+        200 => "OK",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        410 => "Gone",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown"
+    }
+}
@@ -0,0 +1,283 @@
+// Turns a theme into the markers that start Podclaw's output lines, like the green '[✓]'. Colors are written for the
+// best palette the terminal supports, and left out entirely when the output isn't going to a terminal, when 'NO_COLOR'
+// is set, or when the user asks for that.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::error::{Error, Result};
+use crate::structs::TextDeco;
+
+// When to use colors and other escape codes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode
+{
+    #[default]
+    Auto,
+    Always,
+    Never
+}
+
+impl FromStr for ColorMode
+{
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self>
+    {
+        match name
+        {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err(Error::Invalid(format!("Unknown color mode '{}'. It should be 'auto', 'always' or 'never'.", name)))
+        }
+    }
+}
+
+// How many colors the terminal can show.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Palette
+{
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16
+}
+
+// A '[themes.<name>]' section. Each marker is a color, like "#ff8c00" or "bright-red", or a table with a 'color' and a
+// 'symbol'. Markers that aren't set come from the 'base' theme, which is "default" unless given.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeSpec
+{
+    pub base: Option<String>,
+    pub completion: Option<MarkerSpec>,
+    pub general: Option<MarkerSpec>,
+    pub important: Option<MarkerSpec>,
+    pub error: Option<MarkerSpec>,
+    pub prompt: Option<MarkerSpec>,
+    pub input: Option<MarkerSpec>,
+    pub verbose: Option<MarkerSpec>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum MarkerSpec
+{
+    Color(String),
+    Styled { color: Option<String>, symbol: Option<String> }
+}
+
+// A marker's symbol and color, before it's written for a palette.
+#[derive(Clone, Debug, PartialEq)]
+struct Marker
+{
+    symbol: String,
+    color: Option<Color>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Color
+{
+    Rgb(u8, u8, u8),
+
+    // One of the 16 standard terminal colors, which the terminal's own color scheme decides the look of.
+    Standard(u8)
+}
+
+const STANDARD_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+// The usual look of the 16 standard colors, for finding the closest one to an RGB color.
+const STANDARD_RGB: [(u8, u8, u8); 16] =
+[
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255)
+];
+
+impl FromStr for Color
+{
+    type Err = Error;
+
+    fn from_str(raw_color: &str) -> Result<Self>
+    {
+        let raw_color = raw_color.trim().to_lowercase();
+        let invalid = || Error::Invalid(format!("Unknown color '{}'. Use a hex color like '#ff8c00', or a name like 'red' or 'bright-red'.", raw_color));
+
+        if let Some(hex) = raw_color.strip_prefix('#')
+        {
+            if hex.len() != 6 || !hex.is_ascii() { return Err(invalid()) }
+            let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).map_err(|_| invalid());
+            return Ok(Color::Rgb(channel(0..2)?, channel(2..4)?, channel(4..6)?));
+        }
+
+        let (name, offset) = match raw_color.strip_prefix("bright-")
+        {
+            Some(name) => (name, 8),
+            None => (raw_color.as_str(), 0)
+        };
+
+        STANDARD_NAMES.iter().position(|standard_name| *standard_name == name)
+            .map(|index| Color::Standard(index as u8 + offset))
+            .ok_or_else(invalid)
+    }
+}
+
+impl Color
+{
+    // The SGR parameters that set this as the foreground color.
+    fn escape_parameters(self, palette: Palette) -> String
+    {
+        match (self, palette)
+        {
+            (Color::Standard(index), _) => standard_parameters(index),
+            (Color::Rgb(red, green, blue), Palette::TrueColor) => format!("38;2;{};{};{}", red, green, blue),
+            (Color::Rgb(red, green, blue), Palette::Ansi256) => format!("38;5;{}", cube_index(red, green, blue)),
+            (Color::Rgb(red, green, blue), Palette::Ansi16) => standard_parameters(closest_standard(red, green, blue))
+        }
+    }
+}
+
+fn standard_parameters(index: u8) -> String
+{
+    if index < 8 { (30 + index).to_string() } else { (90 + index - 8).to_string() }
+}
+
+// The closest color in the 6x6x6 cube of the 256-color palette. The cube's levels aren't evenly spaced.
+fn cube_index(red: u8, green: u8, blue: u8) -> u8
+{
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |channel: u8| (0..6).min_by_key(|index| CUBE_LEVELS[*index as usize].abs_diff(channel)).unwrap_or(0);
+
+    16 + 36 * level(red) + 6 * level(green) + level(blue)
+}
+
+fn closest_standard(red: u8, green: u8, blue: u8) -> u8
+{
+    let distance = |(standard_red, standard_green, standard_blue): (u8, u8, u8)|
+    {
+        let difference = |first: u8, second: u8| (first as i32 - second as i32).pow(2);
+        difference(red, standard_red) + difference(green, standard_green) + difference(blue, standard_blue)
+    };
+
+    (0..16).min_by_key(|index| distance(STANDARD_RGB[*index as usize])).unwrap_or(7)
+}
+
+
+// The markers of a built-in theme, or nothing if there's no theme with that name.
+fn builtin_markers(name: &str) -> Option<[Marker; 7]>
+{
+    let marker = |symbol: &str, color: Option<Color>| Marker { symbol: symbol.to_string(), color };
+
+    match name
+    {
+        "default" => Some([
+            marker("[✓]", Some(Color::Rgb(51, 153, 51))), // green
+            marker("[*]", Some(Color::Rgb(109, 186, 219))), // light blue
+            marker("[#]", Some(Color::Rgb(255, 140, 0))), // orange
+            marker("[!]", Some(Color::Rgb(255, 50, 50))), // red
+            marker("[?]", Some(Color::Rgb(89, 40, 138))), // darker purple
+            marker("[>]", Some(Color::Rgb(166, 77, 255))), // bright purple
+            marker("[+]", Some(Color::Rgb(72, 135, 195))) // darker blue
+        ]),
+
+        // Uses the terminal's own colors, so it matches light and dark color schemes alike.
+        "basic" => Some([
+            marker("[✓]", Some(Color::Standard(2))),
+            marker("[*]", Some(Color::Standard(14))),
+            marker("[#]", Some(Color::Standard(3))),
+            marker("[!]", Some(Color::Standard(9))),
+            marker("[?]", Some(Color::Standard(5))),
+            marker("[>]", Some(Color::Standard(13))),
+            marker("[+]", Some(Color::Standard(4)))
+        ]),
+
+        "plain" => Some(["[✓]", "[*]", "[#]", "[!]", "[?]", "[>]", "[+]"].map(|symbol| marker(symbol, None))),
+        _ => None
+    }
+}
+
+// Finds a theme's markers, following user themes down to a built-in one. User themes can shadow built-in names.
+fn theme_markers(name: &str, themes: &BTreeMap<String, ThemeSpec>, depth: usize) -> Result<[Marker; 7]>
+{
+    let Some(spec) = themes.get(name) else
+    {
+        return builtin_markers(name).ok_or_else(||
+        {
+            let mut names: Vec<&str> = vec!["default", "basic", "plain"];
+            names.extend(themes.keys().map(String::as_str));
+            Error::Invalid(format!("Unknown theme '{}'. The themes are: {}.", name, names.join(", ")))
+        });
+    };
+
+    // A user theme named like a built-in one can build on the built-in version. Any other loop is a mistake.
+    let base = spec.base.as_deref().unwrap_or("default");
+    let mut markers = match builtin_markers(base).filter(|_| base == name)
+    {
+        Some(builtin) => builtin,
+        None if base == name || depth > themes.len() => return Err(Error::Invalid(format!("The theme '{}' is based on itself.", name))),
+        None => theme_markers(base, themes, depth + 1)?
+    };
+
+    let overrides = [&spec.completion, &spec.general, &spec.important, &spec.error, &spec.prompt, &spec.input, &spec.verbose];
+    for (marker, override_spec) in markers.iter_mut().zip(overrides)
+    {
+        match override_spec
+        {
+            None => {}
+            Some(MarkerSpec::Color(color)) => marker.color = Some(color.parse()?),
+            Some(MarkerSpec::Styled { color, symbol }) =>
+            {
+                if let Some(color) = color { marker.color = Some(color.parse()?) }
+                if let Some(symbol) = symbol { marker.symbol = symbol.clone() }
+            }
+        }
+    }
+
+    Ok(markers)
+}
+
+// Builds the markers for a theme. Without colors, only the symbols are kept.
+pub fn build_text_deco(name: &str, themes: &BTreeMap<String, ThemeSpec>, palette: Option<Palette>) -> Result<TextDeco>
+{
+    let [completion, general, important, error, prompt, input, verbose] = theme_markers(name, themes, 0)?.map(|marker|
+    {
+        match (marker.color, palette)
+        {
+            (Some(color), Some(palette)) => format!("\x1b[1m\x1b[{}m{}\x1b[0m", color.escape_parameters(palette), marker.symbol),
+            _ => marker.symbol
+        }
+    });
+
+    Ok(TextDeco { completion, general, important, error, prompt, input, verbose })
+}
+
+// Whether to use escape codes at all. 'lookup' reads an environment variable. Following https://no-color.org, a
+// non-empty 'NO_COLOR' turns colors off unless they're explicitly asked for.
+pub fn use_colors(mode: ColorMode, is_terminal: bool, lookup: impl Fn(&str) -> Option<String>) -> bool
+{
+    match mode
+    {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto =>
+        {
+            let no_color = lookup("NO_COLOR").is_some_and(|value| !value.is_empty());
+            let dumb_terminal = lookup("TERM").is_some_and(|term| term == "dumb");
+            is_terminal && !no_color && !dumb_terminal
+        }
+    }
+}
+
+// Guesses the palette from the environment, like most terminal programs do.
+pub fn detect_palette(lookup: impl Fn(&str) -> Option<String>) -> Palette
+{
+    let color_term = lookup("COLORTERM").unwrap_or_default().to_lowercase();
+    let term = lookup("TERM").unwrap_or_default().to_lowercase();
+
+    if color_term == "truecolor" || color_term == "24bit" { Palette::TrueColor }
+    else if term.contains("256color") { Palette::Ansi256 }
+    else { Palette::Ansi16 }
+}
@@ -1,30 +1,15 @@
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::fs::{create_dir, File};
+use std::fs::{create_dir_all, File};
+use std::time::Duration;
+use chrono::{DateTime, FixedOffset, Local};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
-use crate::{TXTD};
-use crate::structs::Podcast;
+use crate::error::{Error, Result};
+use crate::structs::{LegacyPodcast, Podcast};
 
-macro_rules! italicize
-{
-    ($string:expr) =>
-    {
-        format!("\x1b[3m{}\x1b[0m", $string)
-    }
-}
-
-
-macro_rules! make_bold
-{
-    ($string:expr) =>
-    {
-        format!("\x1b[1m{}\x1b[0m", $string)
-    }
-}
 
-
-pub fn write_to_bin<T: Serialize>(payload: &T, path: &Path) -> Result<(), Box<dyn std::error::Error>>
+pub fn write_to_bin<T: Serialize + ?Sized>(payload: &T, path: &Path) -> Result<()>
 {
     let mut target_file = File::create(path)?;
     let encoded_payload: Vec<u8> = bincode::serialize(&payload)?;
@@ -35,92 +20,428 @@ pub fn write_to_bin<T: Serialize>(payload: &T, path: &Path) -> Result<(), Box<dy
 }
 
 
-pub fn read_from_bin<T: DeserializeOwned>(path: &Path) -> Result<T, Box<dyn std::error::Error>>
+pub fn read_from_bin<T: DeserializeOwned>(path: &Path) -> Result<T>
 {
     let mut target_file = File::open(path)?;
     let mut retrieved_data: Vec<u8> = Vec::new();
     target_file.read_to_end(&mut retrieved_data)?;
-    //target_file.read_to_string(&mut str_from_ron)?;
     let decoded_data: T = bincode::deserialize(&retrieved_data)?;
 
     Ok(decoded_data)
 }
 
 
-pub fn find_podcast(target_alias: &String, podcasts: &[Podcast]) -> Option<usize>
+// Aliases are case-insensitive, so they're stored and compared in this form. Everything that takes an alias from the
+// user goes through here.
+pub fn normalize_alias(alias: &str) -> String
+{
+    alias.trim().to_lowercase()
+}
+
+// Finds the podcast with exactly this alias.
+pub fn find_podcast(target_alias: &str, podcasts: &[Podcast]) -> Option<usize>
+{
+    let target_alias = normalize_alias(target_alias);
+    podcasts.iter().position(|podcast| podcast.alias == target_alias)
+}
+
+// Finds a podcast by its alias, or by the start of it as long as only one alias starts that way. When nothing matches,
+// the error suggests similar aliases.
+pub fn require_podcast(target_alias: &str, podcasts: &[Podcast]) -> Result<usize>
 {
-    for (index, podcast) in podcasts.iter().enumerate()
+    if let Some(index) = find_podcast(target_alias, podcasts) { return Ok(index) }
+
+    let target_alias = normalize_alias(target_alias);
+    let prefix_matches: Vec<usize> = (0..podcasts.len()).filter(|index| podcasts[*index].alias.starts_with(&target_alias)).collect();
+
+    match prefix_matches.as_slice()
     {
-        if target_alias == &podcast.alias
+        [index] if !target_alias.is_empty() => Ok(*index),
+        [_, _, ..] if !target_alias.is_empty() =>
+        {
+            let aliases: Vec<&str> = prefix_matches.iter().map(|index| podcasts[*index].alias.as_str()).collect();
+            Err(Error::Invalid(format!("'{}' could be any of these podcasts: {}. Type more of the alias!", target_alias, aliases.join(", "))))
+        }
+        _ =>
         {
-            return Some(index);
+            let suggestions = similar_aliases(&target_alias, podcasts);
+            let hint = if suggestions.is_empty() { String::new() } else { format!(" Did you mean {}?", suggestions.join(" or ")) };
+            Err(Error::NotFound(format!("There is no podcast with the alias '{}'.{}", target_alias, hint)))
         }
     }
+}
+
+// Up to three aliases that are only a few typos away, closest first.
+fn similar_aliases(target_alias: &str, podcasts: &[Podcast]) -> Vec<String>
+{
+    let max_distance = (target_alias.chars().count() / 3).max(2);
+
+    let mut candidates: Vec<(usize, &str)> = podcasts.iter()
+        .map(|podcast| (edit_distance(target_alias, &podcast.alias), podcast.alias.as_str()))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    candidates.sort();
 
-    None
+    candidates.into_iter().take(3).map(|(_, alias)| format!("'{}'", alias)).collect()
 }
 
-pub fn get_storage() -> Vec<Podcast>
+// The Levenshtein distance: how many characters have to be inserted, removed or replaced to turn one string into the other.
+pub fn edit_distance(first: &str, second: &str) -> usize
 {
-    if let Ok(storage) = read_from_bin::<Vec<Podcast>>(Path::new(get_storage_path().as_path()))
-    {storage}
+    let second: Vec<char> = second.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=second.len()).collect();
 
-    else if let Ok(does_storage_exist) = get_storage_path().try_exists()
+    for (first_index, first_character) in first.chars().enumerate()
     {
-        if does_storage_exist == false
+        let mut current_row = vec![first_index + 1];
+
+        for (second_index, second_character) in second.iter().enumerate()
         {
-            println!("{} Creating storage...", TXTD.general);
+            let substitution = previous_row[second_index] + usize::from(first_character != *second_character);
+            current_row.push(substitution.min(previous_row[second_index + 1] + 1).min(current_row[second_index] + 1));
+        }
+
+        previous_row = current_row;
+    }
+
+    previous_row[second.len()]
+}
 
-            let new_storage: Vec<Podcast> = Vec::new();
-            write_to_bin(&new_storage, get_storage_path().as_path()).expect(format!("Failed to repair \'{}\'", get_storage_path().to_str().unwrap()).as_str());
+// Resolves what commands like 'update' and 'remove' are pointed at. Each selector is an alias, the start of one, or a
+// glob pattern like 'news-*'. Podcasts are returned in the order they were selected, each only once.
+pub fn select_podcasts(selectors: &[String], podcasts: &[Podcast]) -> Result<Vec<usize>>
+{
+    let mut selected: Vec<usize> = Vec::new();
 
-            println!("{} Done!", TXTD.completion);
-            new_storage
+    for selector in selectors
+    {
+        let matches = if is_glob(selector)
+        {
+            let pattern = normalize_alias(selector);
+            let matches: Vec<usize> = (0..podcasts.len()).filter(|index| glob_matches(&pattern, &podcasts[*index].alias)).collect();
+            if matches.is_empty() { return Err(Error::NotFound(format!("No podcast's alias matches '{}'.", pattern))) }
+            matches
         }
+        else { vec![require_podcast(selector, podcasts)?] };
+
+        for index in matches { if !selected.contains(&index) { selected.push(index) } }
+    }
+
+    Ok(selected)
+}
 
-        else
+pub fn is_glob(selector: &str) -> bool
+{
+    selector.contains(['*', '?'])
+}
+
+// '*' matches any run of characters, and '?' any single one.
+pub fn glob_matches(pattern: &str, text: &str) -> bool
+{
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut pattern_index, mut text_index) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None;
+
+    while text_index < text.len()
+    {
+        match pattern.get(pattern_index)
         {
-            panic!("{} Storage seems invalid. Try running the 'repair' command!", TXTD.error);
+            Some('*') =>
+            {
+                last_star = Some((pattern_index, text_index));
+                pattern_index += 1;
+            }
+            Some(character) if *character == '?' || *character == text[text_index] =>
+            {
+                pattern_index += 1;
+                text_index += 1;
+            }
+            // Let the last star swallow one more character and try again from there.
+            _ => match last_star
+            {
+                Some((star_index, star_text_index)) =>
+                {
+                    last_star = Some((star_index, star_text_index + 1));
+                    pattern_index = star_index + 1;
+                    text_index = star_text_index + 1;
+                }
+                None => return false
+            }
         }
     }
 
+    pattern[pattern_index..].iter().all(|character| *character == '*')
+}
+
+// Aliases end up in shell commands and file names, so they're kept to lowercase letters, digits, '-', '_' and '.'.
+const MAX_ALIAS_LENGTH: usize = 64;
+
+pub fn validate_alias(alias: &str) -> Result<()>
+{
+    if is_valid_name(alias) { Ok(()) }
     else
     {
-        panic!("{} Storage seems invalid. Try running the 'repair' command!", TXTD.error);
+        Err(Error::Invalid(format!("Invalid alias '{}'. Aliases start with a letter or digit, only use lowercase letters, digits, '-', '_' and '.', and are at most {} characters long.", alias, MAX_ALIAS_LENGTH)))
     }
 }
 
-pub fn get_storage_path() -> PathBuf
+// Tags follow the same rules as aliases, since they're typed in the same places.
+pub fn validate_tag(tag: &str) -> Result<()>
 {
-    return if let Some(config_path) = dirs::config_dir()
+    if is_valid_name(tag) { Ok(()) }
+    else
     {
-        let mut storage_path = config_path.join(Path::new("podclaw"));
+        Err(Error::Invalid(format!("Invalid tag '{}'. Tags start with a letter or digit, only use lowercase letters, digits, '-', '_' and '.', and are at most {} characters long.", tag, MAX_ALIAS_LENGTH)))
+    }
+}
 
-        if !storage_path.try_exists().unwrap()
-        { create_dir(storage_path.clone()).expect("Failed to create Podclaw's config path.") }
+fn is_valid_name(name: &str) -> bool
+{
+    name.len() <= MAX_ALIAS_LENGTH
+        && name.starts_with(|character: char| character.is_ascii_lowercase() || character.is_ascii_digit())
+        && name.chars().all(|character| character.is_ascii_lowercase() || character.is_ascii_digit() || "-_.".contains(character))
+}
+
+// Lowercases and checks tags, dropping repeats. They're case-insensitive like aliases.
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>>
+{
+    let mut normalized_tags: Vec<String> = Vec::new();
 
-        storage_path = storage_path.join(Path::new("podclaw_storage.bin"));
-        storage_path
+    for tag in tags
+    {
+        let tag = normalize_alias(tag);
+        validate_tag(&tag)?;
+        if !normalized_tags.contains(&tag) { normalized_tags.push(tag) }
     }
 
-    else
+    Ok(normalized_tags)
+}
+
+// The podcasts that have any of these tags, in storage order. A tag that no podcast has is most likely a typo.
+pub fn podcasts_tagged(tags: &[String], podcasts: &[Podcast]) -> Result<Vec<usize>>
+{
+    let tags = normalize_tags(tags)?;
+
+    if let Some(unused_tag) = tags.iter().find(|tag| !podcasts.iter().any(|podcast| podcast.tags.contains(tag)))
     {
-        println!("{} Can't find config directory on this system, keeping storage file in working directory...", TXTD.important);
-        return PathBuf::from("podclaw_storage.bin");
+        return Err(Error::NotFound(format!("No podcast is tagged '{}'.", unused_tag)));
     }
+
+    Ok((0..podcasts.len()).filter(|index| podcasts[*index].tags.iter().any(|tag| tags.contains(tag))).collect())
 }
 
-pub fn repair_storage(confirmation: bool)
+// Every tag in use, alphabetically, with how many podcasts have it.
+pub fn all_tags(podcasts: &[Podcast]) -> Vec<(String, usize)>
 {
-    if confirmation
+    let mut tags: Vec<(String, usize)> = Vec::new();
+
+    for tag in podcasts.iter().flat_map(|podcast| podcast.tags.iter())
     {
-        println!("{} Repairing storage...", TXTD.general);
+        match tags.iter_mut().find(|(existing_tag, _)| existing_tag == tag)
+        {
+            Some((_, count)) => *count += 1,
+            None => tags.push((tag.clone(), 1))
+        }
+    }
+
+    tags.sort();
+    tags
+}
+
+// Turns a title into an alias that isn't taken yet, like 'The Daily' into 'the-daily', or 'the-daily-2' if that exists.
+pub fn alias_from_title(title: &str, podcasts: &[Podcast]) -> String
+{
+    const MAX_SLUG_LENGTH: usize = 32;
+
+    let mut slug = String::new();
+    for character in title.chars()
+    {
+        if character.is_ascii_alphanumeric() { slug.push(character.to_ascii_lowercase()) }
+        else if !slug.is_empty() && !slug.ends_with('-') { slug.push('-') }
+
+        if slug.len() == MAX_SLUG_LENGTH { break }
+    }
+
+    let slug = match slug.trim_end_matches('-')
+    {
+        "" => "podcast",
+        trimmed_slug => trimmed_slug
+    };
+
+    let mut alias = slug.to_string();
+    let mut suffix = 2;
+    while find_podcast(&alias, podcasts).is_some()
+    {
+        alias = format!("{}-{}", slug, suffix);
+        suffix += 1;
+    }
 
+    alias
+}
+
+// Storage files start with this marker, followed by a format version. Files without it come from Podclaw 1.0.1.
+// bincode isn't self-describing, so once a release has written this format, any change to the layout of 'Podcast' needs
+// a new version, with the old layout kept in 'structs' for reading older files, like 'LegacyPodcast'.
+const STORAGE_MAGIC: &[u8; 4] = b"PCLW";
+const STORAGE_VERSION: u32 = 2;
+
+// Reads the storage file, creating an empty one if it doesn't exist yet.
+pub fn get_storage() -> Result<Vec<Podcast>>
+{
+    get_storage_at(&get_storage_path()?)
+}
+
+pub fn get_storage_at(path: &Path) -> Result<Vec<Podcast>>
+{
+    if !path.try_exists()?
+    {
         let new_storage: Vec<Podcast> = Vec::new();
-        write_to_bin(&new_storage, get_storage_path().as_path()).expect(format!("Failed to repair \'{}\'", get_storage_path().to_str().unwrap()).as_str());
+        save_storage_at(&new_storage, path)?;
+        return Ok(new_storage);
+    }
+
+    let mut retrieved_data: Vec<u8> = Vec::new();
+    File::open(path)?.read_to_end(&mut retrieved_data)?;
+
+    match retrieved_data.strip_prefix(STORAGE_MAGIC)
+    {
+        Some(versioned_data) =>
+        {
+            let (version, podcast_data) = bincode::deserialize::<u32>(versioned_data)
+                .map(|version| (version, &versioned_data[4..]))?;
+
+            if version > STORAGE_VERSION
+            {
+                return Err(Error::Storage(format!("Storage was written by a newer version of Podclaw (format {}).", version)));
+            }
+
+            Ok(with_normalized_aliases(bincode::deserialize::<Vec<Podcast>>(podcast_data)?))
+        }
+
+        None =>
+        {
+            let legacy_podcasts = bincode::deserialize::<Vec<LegacyPodcast>>(&retrieved_data)?;
+            Ok(with_normalized_aliases(legacy_podcasts.into_iter().map(Podcast::from).collect()))
+        }
+    }
+}
+
+// Older versions stored edited aliases as they were typed, which made those podcasts impossible to find.
+fn with_normalized_aliases(mut podcasts: Vec<Podcast>) -> Vec<Podcast>
+{
+    for podcast in &mut podcasts { podcast.alias = normalize_alias(&podcast.alias) }
+    podcasts
+}
+
+pub fn save_storage(podcasts: &[Podcast]) -> Result<()>
+{
+    save_storage_at(podcasts, &get_storage_path()?)
+}
+
+pub fn save_storage_at(podcasts: &[Podcast], path: &Path) -> Result<()>
+{
+    let mut encoded_payload: Vec<u8> = STORAGE_MAGIC.to_vec();
+    encoded_payload.extend(bincode::serialize(&STORAGE_VERSION)?);
+    encoded_payload.extend(bincode::serialize(podcasts)?);
+
+    File::create(path)?.write_all(&encoded_payload)?;
+    Ok(())
+}
+
+// Podclaw keeps everything in its own config folder, or the working directory on systems without one.
+// Setting 'PODCLAW_CONFIG_DIR' moves it somewhere else entirely.
+pub fn get_config_dir() -> Result<PathBuf>
+{
+    if let Some(override_path) = std::env::var_os("PODCLAW_CONFIG_DIR")
+    {
+        let podclaw_path = PathBuf::from(override_path);
+        create_dir_all(&podclaw_path)
+            .map_err(|error| Error::Storage(format!("Failed to create Podclaw's config path: {}", error)))?;
+
+        Ok(podclaw_path)
+    }
+
+    else if let Some(config_path) = dirs::config_dir()
+    {
+        let podclaw_path = config_path.join(Path::new("podclaw"));
 
-        println!("{} Done!", TXTD.completion);
+        if !podclaw_path.try_exists()?
+        {
+            create_dir_all(&podclaw_path)
+                .map_err(|error| Error::Storage(format!("Failed to create Podclaw's config path: {}", error)))?;
+        }
+
+        Ok(podclaw_path)
     }
 
-    else { println!("{} No confirmation flag was set.", TXTD.error) }
-}
\ No newline at end of file
+    else { Ok(PathBuf::new()) }
+}
+
+pub fn get_storage_path() -> Result<PathBuf>
+{
+    Ok(get_config_dir()?.join(Path::new("podclaw_storage.bin")))
+}
+
+// Replaces the storage file with an empty one. This deletes every registered podcast.
+pub fn repair_storage() -> Result<()>
+{
+    let new_storage: Vec<Podcast> = Vec::new();
+    save_storage(&new_storage)
+}
+
+
+pub fn parse_pub_date(raw_date: &str) -> Option<DateTime<FixedOffset>>
+{
+    DateTime::parse_from_rfc2822(raw_date.trim()).ok()
+}
+
+// Shows a pubDate in the local timezone, or as-is if it can't be parsed.
+pub fn format_local_date(raw_date: &str) -> String
+{
+    match parse_pub_date(raw_date)
+    {
+        Some(date) => date.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+        None => raw_date.to_string()
+    }
+}
+
+// Parses an itunes:duration, which can be plain seconds, 'MM:SS', or 'HH:MM:SS'. Durations too long to count are
+// treated like unreadable ones, since they come straight from the feed.
+pub fn parse_duration(raw_duration: &str) -> Option<Duration>
+{
+    let mut total_seconds: u64 = 0;
+
+    for part in raw_duration.trim().split(':')
+    {
+        let value = part.trim().split('.').next()?.parse::<u64>().ok()?;
+        total_seconds = total_seconds.checked_mul(60)?.checked_add(value)?;
+    }
+
+    Some(Duration::from_secs(total_seconds))
+}
+
+pub fn format_duration(duration: Duration) -> String
+{
+    let seconds = duration.as_secs();
+
+    if seconds >= 3600 { format!("{}h {:02}m {:02}s", seconds / 3600, (seconds % 3600) / 60, seconds % 60) }
+    else { format!("{}m {:02}s", seconds / 60, seconds % 60) }
+}
+
+pub fn format_bytes(bytes: u64) -> String
+{
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1
+    {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 { format!("{} {}", bytes, UNITS[0]) }
+    else { format!("{:.1} {}", size, UNITS[unit]) }
+}
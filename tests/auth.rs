@@ -0,0 +1,129 @@
+mod common;
+
+use std::path::PathBuf;
+use podclaw::Error;
+use podclaw::auth::{parse_header, AuthenticatedClient, FeedAuth, FeedCredentials, Secret};
+use podclaw::manage_feeds::*;
+use podclaw::secrets::SecretStore;
+use podclaw::testing::{FakeClient, FakeResponse, TestServer};
+use crate::common::{podclaw, sample_feed, stdout_of};
+
+const FEED_URL: &str = "https://example.com/private.xml";
+
+fn private_credentials() -> FeedCredentials
+{
+    FeedCredentials
+    {
+        auth: FeedAuth::Basic { username: String::from("listener"), password: Secret::Plain(String::from("hunter2")) },
+        headers: vec![(String::from("X-Api-Key"), Secret::Stored(String::from("api-key")))]
+    }
+}
+
+
+#[test]
+fn credentials_are_sent_with_feeds_and_episodes()
+{
+    let client = FakeClient::new()
+        .with_response(FEED_URL, FakeResponse::ok(sample_feed("https://example.com")))
+        .with_response("https://example.com/episode-1.mp3", FakeResponse::ok("audio"));
+    let mut secrets = SecretStore::new();
+    secrets.set("api-key", "abc123").unwrap();
+    let authenticated_client = AuthenticatedClient::new(&client, &private_credentials(), &secrets).unwrap();
+
+    let mut podcasts = Vec::new();
+    let new = NewPodcast { alias: String::from("private"), feed_url: FEED_URL.to_string(), download_path: PathBuf::from("Podcasts"), interval: 1, credentials: private_credentials(), ..Default::default() };
+    add_podcast(&authenticated_client, new, &mut podcasts).unwrap();
+
+    let folder = tempfile::tempdir().unwrap();
+    download_file(&authenticated_client, "https://example.com/episode-1.mp3", &folder.path().join("episode.mp3")).unwrap();
+
+    for request in client.requests()
+    {
+        assert!(request.headers.contains(&(String::from("Authorization"), String::from("Basic bGlzdGVuZXI6aHVudGVyMg=="))));
+        assert!(request.headers.contains(&(String::from("X-Api-Key"), String::from("abc123"))));
+    }
+    assert_eq!(client.requests().len(), 2);
+
+    let missing_secret = AuthenticatedClient::new(&client, &private_credentials(), &SecretStore::new());
+    assert!(matches!(missing_secret, Err(Error::NotFound(_))));
+}
+
+#[test]
+fn credentials_stay_behind_on_redirects_to_other_hosts_or_plain_http()
+{
+    let client = FakeClient::new()
+        .with_response("https://example.com/episode-1.mp3", FakeResponse::status(302).with_header("Location", "https://cdn.example.net/episode-1.mp3"))
+        .with_response("https://cdn.example.net/episode-1.mp3", FakeResponse::ok("audio"))
+        .with_response("https://example.com/episode-2.mp3", FakeResponse::status(302).with_header("Location", "http://example.com/episode-2.mp3"))
+        .with_response("http://example.com/episode-2.mp3", FakeResponse::ok("audio"))
+        .with_response("https://example.com/episode-3.mp3", FakeResponse::status(302).with_header("Location", "/files/episode-3.mp3"))
+        .with_response("https://example.com/files/episode-3.mp3", FakeResponse::ok("audio"));
+    let mut secrets = SecretStore::new();
+    secrets.set("api-key", "abc123").unwrap();
+    let authenticated_client = AuthenticatedClient::new(&client, &private_credentials(), &secrets).unwrap();
+
+    let folder = tempfile::tempdir().unwrap();
+    for episode in ["episode-1", "episode-2", "episode-3"]
+    {
+        download_file(&authenticated_client, &format!("https://example.com/{}.mp3", episode), &folder.path().join(format!("{}.mp3", episode))).unwrap();
+    }
+
+    let requests = client.requests();
+    let sent_header = |url: &str, header: &str| requests.iter()
+        .find(|request| request.url == url).unwrap()
+        .headers.iter().any(|(name, _)| name.eq_ignore_ascii_case(header));
+
+    for header in ["X-Api-Key", "Authorization"]
+    {
+        assert!(sent_header("https://example.com/episode-1.mp3", header));
+        assert!(!sent_header("https://cdn.example.net/episode-1.mp3", header));
+        assert!(!sent_header("http://example.com/episode-2.mp3", header));
+        assert!(sent_header("https://example.com/files/episode-3.mp3", header));
+    }
+}
+
+#[test]
+fn edits_replace_and_clear_credentials()
+{
+    let client = FakeClient::new().with_response(FEED_URL, FakeResponse::ok(sample_feed("https://example.com")));
+    let mut podcasts = Vec::new();
+    let new = NewPodcast { alias: String::from("private"), feed_url: FEED_URL.to_string(), credentials: private_credentials(), ..Default::default() };
+    add_podcast(&client, new, &mut podcasts).unwrap();
+
+    let (name, value) = parse_header("x-api-key: def456").unwrap();
+    let edit = PodcastEdit
+    {
+        auth: Some(FeedAuth::Bearer { token: Secret::Stored(String::from("token")) }),
+        headers: vec![(name, Secret::Plain(value))],
+        ..Default::default()
+    };
+    edit_podcast(0, &mut podcasts, edit).unwrap();
+    assert_eq!(podcasts[0].credentials.auth, FeedAuth::Bearer { token: Secret::Stored(String::from("token")) });
+    assert_eq!(podcasts[0].credentials.headers, vec![(String::from("x-api-key"), Secret::Plain(String::from("def456")))]);
+
+    edit_podcast(0, &mut podcasts, PodcastEdit { auth: Some(FeedAuth::None), reset_headers: true, ..Default::default() }).unwrap();
+    assert!(podcasts[0].credentials.is_empty());
+}
+
+#[test]
+fn secrets_are_hidden_unless_requested()
+{
+    let server = TestServer::start();
+    server.set_route("/feed.xml", FakeResponse::ok(sample_feed(&server.url(""))));
+    let config = tempfile::tempdir().unwrap();
+
+    let added = podclaw(config.path(), &["add", "private", &server.url("/feed.xml"), "Podcasts", "24",
+        "--username", "listener", "--password", "hunter2", "--header", "X-Api-Key: abc123"]);
+    assert!(added.status.success());
+    assert!(!stdout_of(&added).contains("hunter2"));
+    assert!(server.requests().iter().all(|request| request.headers.iter().any(|(name, _)| name == "authorization")));
+
+    let inspected = stdout_of(&podclaw(config.path(), &["inspect", "private"]));
+    assert!(inspected.contains("listener"));
+    assert!(!inspected.contains("hunter2") && !inspected.contains("abc123"));
+
+    let revealed = stdout_of(&podclaw(config.path(), &["inspect", "private", "--show-secrets"]));
+    assert!(revealed.contains("hunter2") && revealed.contains("abc123"));
+
+    assert_eq!(podclaw(config.path(), &["edit", "private", "--header", "no colon"]).status.code(), Some(1));
+}
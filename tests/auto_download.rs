@@ -0,0 +1,110 @@
+mod common;
+
+use std::time::{Duration, SystemTime};
+use podclaw::Error;
+use podclaw::auto_download::*;
+use podclaw::config::DEFAULT_FILENAME_TEMPLATE;
+use podclaw::filters::parse_date_bound;
+use podclaw::manage_feeds::*;
+use podclaw::structs::Podcast;
+use podclaw::testing::{FakeClient, FakeResponse};
+use crate::common::sample_feed;
+
+const FEED_URL: &str = "https://example.com/feed.xml";
+
+fn client_with_episodes() -> FakeClient
+{
+    FakeClient::new()
+        .with_response(FEED_URL, FakeResponse::ok(sample_feed("https://example.com")))
+        .with_response("https://example.com/episode-1.mp3", FakeResponse::ok("one"))
+        .with_response("https://example.com/episode-2.mp3", FakeResponse::ok("two"))
+}
+
+fn since_january() -> Option<SystemTime>
+{
+    Some(SystemTime::from(parse_date_bound("2026-01-01").unwrap()))
+}
+
+fn rules(edit: AutoDownloadEdit) -> AutoDownload
+{
+    let mut rules = AutoDownload::default();
+    rules.apply(AutoDownloadEdit { enabled: Some(true), since: since_january(), ..edit }).unwrap();
+    rules
+}
+
+
+#[test]
+fn rules_pick_new_matching_episodes()
+{
+    let client = client_with_episodes();
+    let mut podcasts = Vec::new();
+    add_podcast(&client, NewPodcast { alias: String::from("daily"), feed_url: FEED_URL.to_string(), interval: 1, ..Default::default() }, &mut podcasts).unwrap();
+    let episodes = ordered_episodes(&load_cached_feed(&podcasts[0]).unwrap(), EpisodeOrder::Oldest);
+    let nothing_downloaded = |_: usize, _: &rss::Item| false;
+
+    // Turning the rules on without a date only counts episodes published from then on.
+    let mut fresh = AutoDownload::default();
+    fresh.apply(AutoDownloadEdit { enabled: Some(true), ..Default::default() }).unwrap();
+    assert!(fresh.since.is_some());
+    assert!(fresh.plan(&episodes, nothing_downloaded).unwrap().download.is_empty());
+
+    assert_eq!(rules(AutoDownloadEdit::default()).plan(&episodes, nothing_downloaded).unwrap().download, vec![0, 1]);
+    assert_eq!(rules(AutoDownloadEdit { include_pattern: Some(String::from("two")), ..Default::default() }).plan(&episodes, nothing_downloaded).unwrap().download, vec![1]);
+    assert_eq!(rules(AutoDownloadEdit { exclude_pattern: Some(String::from("two")), ..Default::default() }).plan(&episodes, nothing_downloaded).unwrap().download, vec![0]);
+    assert_eq!(rules(AutoDownloadEdit { max_duration: Some(Duration::from_secs(45 * 60)), ..Default::default() }).plan(&episodes, nothing_downloaded).unwrap().download, vec![0]);
+    assert_eq!(rules(AutoDownloadEdit { keep_latest: Some(1), ..Default::default() }).plan(&episodes, nothing_downloaded).unwrap().download, vec![1]);
+
+    // Only episodes the rules downloaded themselves are ever deleted.
+    let mut kept = rules(AutoDownloadEdit { keep_latest: Some(1), ..Default::default() });
+    assert!(kept.plan(&episodes, |_, _| true).unwrap().remove.is_empty());
+    kept.downloaded.push(String::from("episode-1"));
+    assert_eq!(kept.plan(&episodes, |_, _| true).unwrap(), AutoDownloadPlan { download: Vec::new(), remove: vec![0] });
+}
+
+#[test]
+fn auto_download_fetches_and_prunes_episodes()
+{
+    let client = client_with_episodes();
+    let folder = tempfile::tempdir().unwrap();
+    let mut podcast = Podcast { alias: String::from("daily"), feed_url: FEED_URL.to_string(), download_path: folder.path().to_path_buf(), ..Podcast::new() };
+    update_podcast(&client, &mut podcast).unwrap();
+
+    assert!(run_auto_download(&client, &mut podcast, DEFAULT_FILENAME_TEMPLATE).unwrap().downloaded.is_empty());
+
+    podcast.auto_download = rules(AutoDownloadEdit::default());
+    let report = run_auto_download(&client, &mut podcast, DEFAULT_FILENAME_TEMPLATE).unwrap();
+    assert_eq!(report.downloaded.len(), 2);
+    assert!(report.failures.is_empty());
+    assert_eq!(podcast.auto_download.downloaded, vec!["episode-1", "episode-2"]);
+
+    // Files already there aren't fetched again.
+    let requests = client.requests().len();
+    assert!(run_auto_download(&client, &mut podcast, DEFAULT_FILENAME_TEMPLATE).unwrap().downloaded.is_empty());
+    assert_eq!(client.requests().len(), requests);
+
+    podcast.auto_download.apply(AutoDownloadEdit { keep_latest: Some(1), ..Default::default() }).unwrap();
+    let report = run_auto_download(&client, &mut podcast, DEFAULT_FILENAME_TEMPLATE).unwrap();
+    assert_eq!(report.removed.len(), 1);
+    assert!(!report.removed[0].exists());
+    assert!(report.removed[0].display().to_string().contains("Episode One"));
+    assert_eq!(podcast.auto_download.downloaded, vec!["episode-2"]);
+}
+
+#[test]
+fn edit_rejects_a_bad_title_pattern()
+{
+    let client = client_with_episodes();
+    let mut podcasts = Vec::new();
+    add_podcast(&client, NewPodcast { alias: String::from("daily"), feed_url: FEED_URL.to_string(), interval: 1, ..Default::default() }, &mut podcasts).unwrap();
+
+    let edit = PodcastEdit
+    {
+        alias: Some(String::from("renamed")),
+        auto_download: AutoDownloadEdit { enabled: Some(true), include_pattern: Some(String::from("(unclosed")), ..Default::default() },
+        ..Default::default()
+    };
+
+    assert!(matches!(edit_podcast(0, &mut podcasts, edit), Err(Error::Invalid(_))));
+    assert_eq!(podcasts[0].alias, "daily");
+    assert!(!podcasts[0].auto_download.enabled);
+}
@@ -0,0 +1,90 @@
+// Shared fixtures for Podclaw's integration tests.
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Output, Stdio};
+
+// A small feed with two downloadable episodes, listed newest first like most real feeds.
+pub fn sample_feed(base_url: &str) -> String
+{
+    format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd" xmlns:podcast="https://podcastindex.org/namespace/1.0">
+<channel>
+<title>The Daily Test</title>
+<link>{base_url}/</link>
+<description>A show made for tests.</description>
+<itunes:author>Tester</itunes:author>
+<item>
+<title>Episode Two</title>
+<description>The second episode.</description>
+<pubDate>Tue, 03 Feb 2026 10:00:00 +0000</pubDate>
+<guid>episode-2</guid>
+<enclosure url="{base_url}/episode-2.mp3" length="2048" type="audio/mpeg"/>
+<itunes:duration>01:02:03</itunes:duration>
+<podcast:chapters url="{base_url}/episode-2.json" type="application/json+chapters"/>
+<podcast:transcript url="{base_url}/episode-2.srt" type="application/srt"/>
+</item>
+<item>
+<title>Episode One</title>
+<description>The first episode.</description>
+<pubDate>Mon, 02 Feb 2026 10:00:00 +0000</pubDate>
+<guid>episode-1</guid>
+<enclosure url="{base_url}/episode-1.mp3" length="1024" type="audio/mpeg"/>
+<itunes:duration>1800</itunes:duration>
+</item>
+</channel>
+</rss>"#)
+}
+
+// Runs the podclaw binary with its config folder moved into the given directory.
+pub fn podclaw(config_dir: &Path, args: &[&str]) -> Output
+{
+    podclaw_with_env(config_dir, args, &[])
+}
+
+// Runs Podclaw with extra environment variables, like the secrets passphrase.
+pub fn podclaw_with_env(config_dir: &Path, args: &[&str], variables: &[(&str, &str)]) -> Output
+{
+    podclaw_command(config_dir, args, variables).output().expect("Failed to run podclaw.")
+}
+
+// Runs Podclaw with something piped into it, like the output of another command.
+pub fn podclaw_with_input(config_dir: &Path, args: &[&str], input: &str) -> Output
+{
+    let mut child = podclaw_command(config_dir, args, &[])
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run podclaw.");
+
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().expect("Failed to run podclaw.")
+}
+
+fn podclaw_command(config_dir: &Path, args: &[&str], variables: &[(&str, &str)]) -> Command
+{
+    let mut command = Command::new(env!("CARGO_BIN_EXE_podclaw"));
+    for (name, _) in std::env::vars().filter(|(name, _)| name.starts_with("PODCLAW_")) { command.env_remove(name); }
+
+    command
+        .args(args)
+        .env("PODCLAW_CONFIG_DIR", config_dir)
+        .envs(variables.iter().copied())
+        .env_remove("HTTP_PROXY").env_remove("HTTPS_PROXY").env_remove("ALL_PROXY")
+        .env_remove("http_proxy").env_remove("https_proxy").env_remove("all_proxy");
+    command
+}
+
+// Starts Podclaw without waiting for it, for commands that keep running, like 'daemon'.
+pub fn spawn_podclaw(config_dir: &Path, args: &[&str]) -> Child
+{
+    podclaw_command(config_dir, args, &[])
+        .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to run podclaw.")
+}
+
+pub fn stdout_of(output: &Output) -> String
+{
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+use podclaw::Error;
+use podclaw::config::{parse_config, DEFAULT_FILENAME_TEMPLATE};
+
+#[test]
+fn defaults_apply_when_the_file_sets_nothing()
+{
+    let config = parse_config("").unwrap();
+
+    assert_eq!(config.interval(), 24);
+    assert_eq!(config.filename_template(), DEFAULT_FILENAME_TEMPLATE);
+    assert_eq!(config.concurrency(), 1);
+    assert_eq!(config.download_root(), None);
+}
+
+#[test]
+fn environment_variables_override_the_file()
+{
+    let mut config = parse_config("[defaults]\ninterval = 6\nconcurrency = 2\ndownload_root = \"/podcasts\"\n\n[network]\nretries = 1\n").unwrap();
+
+    let variables = [("PODCLAW_INTERVAL", "12"), ("PODCLAW_RETRIES", "5"), ("PODCLAW_RETRY_BACKOFF", "250"), ("PODCLAW_THEME", "plain")];
+    config.apply_environment(|name| variables.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())).unwrap();
+
+    assert_eq!(config.interval(), 12);
+    assert_eq!(config.concurrency(), 2);
+    assert_eq!(config.network.retries, Some(5));
+    assert_eq!(config.network.retry_backoff, Some(250));
+    assert_eq!(config.output.theme.as_deref(), Some("plain"));
+    assert_eq!(config.download_root(), Some(PathBuf::from("/podcasts")));
+
+    let bad_number = config.apply_environment(|name| (name == "PODCLAW_CONCURRENCY").then(|| String::from("many")));
+    assert!(matches!(bad_number, Err(Error::Invalid(_))));
+}
+
+#[test]
+fn download_root_expands_the_home_folder()
+{
+    let config = parse_config("[defaults]\ndownload_root = \"~/Podcasts\"\n").unwrap();
+    assert_eq!(config.download_root(), dirs::home_dir().map(|home_dir| home_dir.join("Podcasts")));
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::path::PathBuf;
+use podclaw::Error;
+use podclaw::discovery::{discover_feeds, FeedChooser};
+use podclaw::manage_feeds::*;
+use podclaw::testing::{FakeClient, FakeResponse};
+use crate::common::sample_feed;
+
+const PAGE_URL: &str = "https://example.com/shows/daily/";
+
+fn page(links: &str) -> String
+{
+    format!("<!DOCTYPE html><html><head><title>The Daily Test</title>{}</head><body>Listen now!</body></html>", links)
+}
+
+fn new_podcast(choose_feed: Option<FeedChooser>) -> NewPodcast
+{
+    NewPodcast { alias: String::from("daily"), feed_url: PAGE_URL.to_string(), download_path: PathBuf::from("Podcasts"), interval: 1, choose_feed, ..Default::default() }
+}
+
+
+#[test]
+fn pages_list_their_rss_and_atom_feeds()
+{
+    let html = page(r#"
+        <link rel="stylesheet" href="/style.css">
+        <LINK REL='alternate' TYPE='application/atom+xml' HREF='/atom.xml'>
+        <link rel="alternate" type="application/rss+xml" title="Daily &amp; More" href="feed.xml" />
+        <link rel="alternate" type="application/rss+xml" href="https://cdn.example.com/feed.xml">
+        <linkage rel="alternate" type="application/rss+xml" href="/nope.xml">"#);
+
+    let candidates = discover_feeds(&html, PAGE_URL);
+    let urls: Vec<&str> = candidates.iter().map(|candidate| candidate.url.as_str()).collect();
+
+    assert_eq!(urls, ["https://example.com/shows/daily/feed.xml", "https://cdn.example.com/feed.xml", "https://example.com/atom.xml"]);
+    assert_eq!(candidates[0].title.as_deref(), Some("Daily & More"));
+    assert!(candidates[2].is_atom);
+}
+
+#[test]
+fn add_picks_the_only_feed_on_a_page()
+{
+    let client = FakeClient::new()
+        .with_response(PAGE_URL, FakeResponse::ok(page(r#"<link rel="alternate" type="application/rss+xml" href="/feed.xml">"#)))
+        .with_response("https://example.com/feed.xml", FakeResponse::ok(sample_feed("https://example.com")));
+    let mut podcasts = Vec::new();
+
+    let podcast = add_podcast(&client, new_podcast(None), &mut podcasts).unwrap();
+    assert_eq!(podcast.feed_url, "https://example.com/feed.xml");
+}
+
+#[test]
+fn add_asks_when_a_page_has_several_feeds()
+{
+    let links = r#"<link rel="alternate" type="application/rss+xml" href="/audio.xml"><link rel="alternate" type="application/rss+xml" href="/video.xml">"#;
+    let client = FakeClient::new()
+        .with_response(PAGE_URL, FakeResponse::ok(page(links)))
+        .with_response("https://example.com/video.xml", FakeResponse::ok(sample_feed("https://example.com")));
+    let mut podcasts = Vec::new();
+
+    match add_podcast(&client, new_podcast(None), &mut podcasts)
+    {
+        Err(Error::Invalid(message)) => assert!(message.contains("https://example.com/audio.xml") && message.contains("https://example.com/video.xml")),
+        other => panic!("Expected the feeds to be listed, got {:?}", other.map(|podcast| podcast.alias.clone()))
+    }
+
+    let podcast = add_podcast(&client, new_podcast(Some(|_| Some(1))), &mut podcasts).unwrap();
+    assert_eq!(podcast.feed_url, "https://example.com/video.xml");
+}
+
+#[test]
+fn pages_without_feeds_still_fail_to_parse()
+{
+    let client = FakeClient::new().with_response(PAGE_URL, FakeResponse::ok(page("")));
+    let mut podcasts = Vec::new();
+
+    assert!(matches!(add_podcast(&client, new_podcast(None), &mut podcasts), Err(Error::Parse(_))));
+}
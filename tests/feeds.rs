@@ -0,0 +1,250 @@
+mod common;
+
+use std::path::PathBuf;
+use rss::Channel;
+use podclaw::Error;
+use podclaw::manage_feeds::*;
+use podclaw::podcast_namespace::{PodcastNamespace, Transcript};
+use podclaw::structs::Podcast;
+use podclaw::utils::{edit_distance, glob_matches, require_podcast, select_podcasts};
+use podclaw::testing::{FakeClient, FakeResponse};
+use crate::common::sample_feed;
+
+const FEED_URL: &str = "https://example.com/feed.xml";
+
+fn client_with_feed() -> FakeClient
+{
+    FakeClient::new().with_response(FEED_URL, FakeResponse::ok(sample_feed("https://example.com")))
+}
+
+fn added_podcasts(client: &FakeClient) -> Vec<Podcast>
+{
+    let mut podcasts = Vec::new();
+    add_podcast(client, new_podcast("daily", FEED_URL), &mut podcasts).unwrap();
+    podcasts
+}
+
+fn new_podcast(alias: &str, feed_url: &str) -> NewPodcast
+{
+    NewPodcast { alias: alias.to_string(), feed_url: feed_url.to_string(), download_path: PathBuf::from("Podcasts"), interval: 1, ..Default::default() }
+}
+
+
+#[test]
+fn add_caches_the_feed()
+{
+    let client = client_with_feed();
+    let podcasts = added_podcasts(&client);
+
+    assert_eq!(podcasts.len(), 1);
+    assert_eq!(podcasts[0].alias, "daily");
+    assert_eq!(load_cached_feed(&podcasts[0]).unwrap().items.len(), 2);
+    assert_eq!(client.requests().len(), 1);
+}
+
+#[test]
+fn add_rejects_a_duplicate_alias()
+{
+    let client = client_with_feed();
+    let mut podcasts = added_podcasts(&client);
+
+    let result = add_podcast(&client, new_podcast("daily", FEED_URL), &mut podcasts);
+    assert!(matches!(result, Err(Error::Invalid(_))));
+}
+
+#[test]
+fn add_reports_network_and_parse_errors()
+{
+    let client = FakeClient::new()
+        .with_response("https://example.com/missing.xml", FakeResponse::status(404))
+        .with_response("https://example.com/page.html", FakeResponse::ok("<html>Not a feed</html>"));
+    let mut podcasts = Vec::new();
+
+    let missing = add_podcast(&client, new_podcast("a", "https://example.com/missing.xml"), &mut podcasts);
+    assert!(matches!(missing, Err(Error::Network(_))));
+
+    let unreachable = add_podcast(&client, new_podcast("b", "https://nowhere.invalid/feed.xml"), &mut podcasts);
+    assert!(matches!(unreachable, Err(Error::Network(_))));
+
+    let not_a_feed = add_podcast(&client, new_podcast("c", "https://example.com/page.html"), &mut podcasts);
+    assert!(matches!(not_a_feed, Err(Error::Parse(_))));
+
+    assert!(podcasts.is_empty());
+}
+
+#[test]
+fn update_refreshes_the_cache()
+{
+    let client = client_with_feed();
+    let mut podcasts = added_podcasts(&client);
+
+    client.set_response(FEED_URL, FakeResponse::ok(sample_feed("https://mirror.example.com")));
+    update_podcast(&client, &mut podcasts[0]).unwrap();
+
+    let feed = load_cached_feed(&podcasts[0]).unwrap();
+    assert!(feed.items[0].enclosure.as_ref().unwrap().url.starts_with("https://mirror.example.com"));
+}
+
+#[test]
+fn locked_podcasts_refuse_changes()
+{
+    let client = client_with_feed();
+    let mut podcasts = added_podcasts(&client);
+    assert!(lock_podcast(&mut podcasts[0]));
+
+    assert!(matches!(update_podcast(&client, &mut podcasts[0]), Err(Error::Locked(_))));
+    assert!(matches!(edit_podcast(0, &mut podcasts, PodcastEdit { interval: Some(2), ..Default::default() }), Err(Error::Locked(_))));
+    assert!(!do_autocache(&client, &mut podcasts[0]).unwrap());
+
+    // Locking doesn't stop a podcast from being removed.
+    assert_eq!(remove_podcast(0, &mut podcasts).alias, "daily");
+    assert!(podcasts.is_empty());
+}
+
+#[test]
+fn edit_changes_only_what_was_given()
+{
+    let client = client_with_feed();
+    let mut podcasts = added_podcasts(&client);
+
+    assert!(!edit_podcast(0, &mut podcasts, PodcastEdit::default()).unwrap());
+    assert!(edit_podcast(0, &mut podcasts, PodcastEdit { download_path: Some(PathBuf::from("Elsewhere")), ..Default::default() }).unwrap());
+
+    assert_eq!(podcasts[0].download_path, PathBuf::from("Elsewhere"));
+    assert_eq!(podcasts[0].feed_url, FEED_URL);
+}
+
+#[test]
+fn episodes_are_oldest_first_by_default()
+{
+    let client = client_with_feed();
+    let podcasts = added_podcasts(&client);
+    let feed = load_cached_feed(&podcasts[0]).unwrap();
+
+    let episodes = ordered_episodes(&feed, EpisodeOrder::default());
+    assert_eq!(episodes[0].title.as_deref(), Some("Episode One"));
+    assert!(matches!(select_episode(&episodes, 2), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn episodes_are_sorted_by_date_with_undated_ones_last()
+{
+    let feed = Channel::read_from(r#"<rss version="2.0"><channel><title>Shuffled</title><link>https://example.com/</link><description>Out of order.</description>
+<item><title>Middle</title><pubDate>Tue, 03 Feb 2026 10:00:00 +0000</pubDate></item>
+<item><title>Undated</title><pubDate>last week</pubDate></item>
+<item><title>Newest</title><pubDate>Wed, 04 Feb 2026 10:00:00 +0000</pubDate></item>
+<item><title>Oldest</title><pubDate>Mon, 02 Feb 2026 10:00:00 +0000</pubDate></item>
+</channel></rss>"#.as_bytes()).unwrap();
+    let titles = |order: EpisodeOrder| -> Vec<String>
+    {
+        ordered_episodes(&feed, order).into_iter().map(|episode| episode.title.unwrap()).collect()
+    };
+
+    assert_eq!(titles(EpisodeOrder::Oldest), ["Oldest", "Middle", "Newest", "Undated"]);
+    assert_eq!(titles(EpisodeOrder::Newest), ["Newest", "Middle", "Oldest", "Undated"]);
+    assert_eq!(titles(EpisodeOrder::Feed), ["Middle", "Undated", "Newest", "Oldest"]);
+    assert!(matches!("random".parse::<EpisodeOrder>(), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn download_writes_the_enclosure()
+{
+    let client = client_with_feed().with_response("https://example.com/episode-1.mp3", FakeResponse::ok("audio"));
+    let podcasts = added_podcasts(&client);
+    let folder = tempfile::tempdir().unwrap();
+
+    let destination = folder.path().join("episode.mp3");
+    download_file(&client, "https://example.com/episode-1.mp3", &destination).unwrap();
+    assert_eq!(std::fs::read(&destination).unwrap(), b"audio");
+
+    let missing = download_file(&client, "https://example.com/episode-9.mp3", &folder.path().join("missing.mp3"));
+    assert!(matches!(missing, Err(Error::Network(_))));
+    assert_eq!(podcasts.len(), 1);
+}
+
+#[test]
+fn missing_aliases_come_from_the_title()
+{
+    let client = client_with_feed();
+    let mut podcasts = Vec::new();
+
+    assert_eq!(add_podcast(&client, new_podcast("", FEED_URL), &mut podcasts).unwrap().alias, "the-daily-test");
+    assert_eq!(add_podcast(&client, new_podcast("", FEED_URL), &mut podcasts).unwrap().alias, "the-daily-test-2");
+    assert_eq!(podclaw::utils::alias_from_title("  Café: Late Night!! ", &podcasts), "caf-late-night");
+    assert_eq!(podclaw::utils::alias_from_title("日本語", &podcasts), "podcast");
+}
+
+#[test]
+fn aliases_must_be_shell_friendly()
+{
+    let client = client_with_feed();
+    let mut podcasts = Vec::new();
+
+    for bad_alias in ["my show", "-daily", "daily/news"]
+    {
+        assert!(matches!(add_podcast(&client, new_podcast(bad_alias, FEED_URL), &mut podcasts), Err(Error::Invalid(_))));
+    }
+    assert!(add_podcast(&client, new_podcast("daily_news.2", FEED_URL), &mut podcasts).is_ok());
+    assert_eq!(add_podcast(&client, new_podcast("Daily", FEED_URL), &mut podcasts).unwrap().alias, "daily");
+}
+
+#[test]
+fn aliases_match_by_case_prefix_and_suggestion()
+{
+    let client = client_with_feed();
+    let mut podcasts = Vec::new();
+    for alias in ["daily", "dance", "weekly"] { add_podcast(&client, new_podcast(alias, FEED_URL), &mut podcasts).unwrap(); }
+
+    edit_podcast(2, &mut podcasts, PodcastEdit { alias: Some(String::from(" Weekly-News ")), ..Default::default() }).unwrap();
+    assert_eq!(podcasts[2].alias, "weekly-news");
+    assert_eq!(require_podcast("WEEKLY-NEWS", &podcasts).unwrap(), 2);
+    assert!(matches!(edit_podcast(2, &mut podcasts, PodcastEdit { alias: Some(String::from("Daily")), ..Default::default() }), Err(Error::Invalid(_))));
+
+    assert_eq!(require_podcast("dai", &podcasts).unwrap(), 0);
+    assert!(matches!(require_podcast("da", &podcasts), Err(Error::Invalid(_))));
+
+    match require_podcast("dialy", &podcasts)
+    {
+        Err(Error::NotFound(message)) => assert!(message.contains("Did you mean 'daily'")),
+        other => panic!("Expected a suggestion, got {:?}", other)
+    }
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+}
+
+#[test]
+fn selections_combine_aliases_and_globs()
+{
+    let client = client_with_feed();
+    let mut podcasts = Vec::new();
+    for alias in ["news-am", "news-pm", "daily"] { add_podcast(&client, new_podcast(alias, FEED_URL), &mut podcasts).unwrap(); }
+
+    let selectors = |raw: &[&str]| raw.iter().map(|selector| selector.to_string()).collect::<Vec<String>>();
+    assert_eq!(select_podcasts(&selectors(&["dai", "NEWS-*"]), &podcasts).unwrap(), vec![2, 0, 1]);
+    assert_eq!(select_podcasts(&selectors(&["news-pm", "news-?m"]), &podcasts).unwrap(), vec![1, 0]);
+    assert!(matches!(select_podcasts(&selectors(&["weekly-*"]), &podcasts), Err(Error::NotFound(_))));
+
+    assert!(glob_matches("*-am", "news-am"));
+    assert!(glob_matches("n*s*", "news-pm"));
+    assert!(!glob_matches("news-?", "news-am"));
+}
+
+#[test]
+fn transcripts_of_the_same_type_get_their_own_files()
+{
+    let transcript = |url: &str, language: Option<&str>| Transcript
+    {
+        url: url.to_string(), mime_type: String::from("application/srt"), language: language.map(str::to_string), rel: None
+    };
+    let namespace = PodcastNamespace
+    {
+        transcripts: vec![transcript("en.srt", Some("en")), transcript("es.srt", Some("es")), transcript("plain.srt", None), transcript("plain-2.srt", None)],
+        ..Default::default()
+    };
+    let podcast = Podcast { alias: String::from("daily"), download_path: PathBuf::from("Podcasts"), ..Podcast::new() };
+    let episode = rss::Item { title: Some(String::from("Episode One")), ..Default::default() };
+
+    let names: Vec<String> = transcript_downloads(&podcast, 0, &episode, &namespace, "{title}").into_iter()
+        .map(|(_, path)| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, vec!["Episode One.en.srt", "Episode One.es.srt", "Episode One.srt", "Episode One.2.srt"]);
+}
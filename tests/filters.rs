@@ -0,0 +1,98 @@
+use std::time::Duration;
+use podclaw::Error;
+use podclaw::filters::*;
+use podclaw::utils::parse_duration;
+use rss::Channel;
+
+const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+<channel>
+<title>Mixed</title>
+<link>https://example.com/</link>
+<description>Audio, video and everything in between.</description>
+<item>
+<title>Interview: The Long One</title>
+<pubDate>Wed, 04 Feb 2026 10:00:00 +0000</pubDate>
+<enclosure url="https://example.com/long.mp3" length="0" type="audio/mpeg"/>
+<itunes:duration>1:10:00</itunes:duration>
+</item>
+<item>
+<title>Behind the Scenes</title>
+<pubDate>Tue, 03 Feb 2026 10:00:00 +0000</pubDate>
+<enclosure url="https://example.com/scenes.m4v?source=rss" length="0" type="application/octet-stream"/>
+<itunes:duration>600</itunes:duration>
+</item>
+<item>
+<title>interview: short</title>
+<pubDate>someday</pubDate>
+<enclosure url="https://example.com/short.mp3" length="0" type="audio/mpeg"/>
+<itunes:duration>15:00</itunes:duration>
+</item>
+<item>
+<title>Announcement</title>
+<pubDate>Thu, 01 Jan 2026 10:00:00 +0000</pubDate>
+</item>
+</channel>
+</rss>"#;
+
+fn matching(filter: &EpisodeFilter) -> Vec<usize>
+{
+    let feed = Channel::read_from(FEED.as_bytes()).unwrap();
+    filter.apply(&feed.items)
+}
+
+
+#[test]
+fn feed_durations_parse()
+{
+    assert_eq!(parse_duration("3723"), Some(Duration::from_secs(3723)));
+    assert_eq!(parse_duration("02:03"), Some(Duration::from_secs(123)));
+    assert_eq!(parse_duration("1:02:03.5"), Some(Duration::from_secs(3723)));
+    assert_eq!(parse_duration("soon"), None);
+    assert_eq!(parse_duration("18446744073709551615:59"), None);
+}
+
+#[test]
+fn durations_and_dates_parse()
+{
+    assert_eq!(parse_duration_bound("20m").unwrap(), Duration::from_secs(1200));
+    assert_eq!(parse_duration_bound("1h30m").unwrap(), Duration::from_secs(5400));
+    assert_eq!(parse_duration_bound("90s").unwrap(), Duration::from_secs(90));
+    assert_eq!(parse_duration_bound("45").unwrap(), Duration::from_secs(2700));
+    assert_eq!(parse_duration_bound("1:00:00").unwrap(), Duration::from_secs(3600));
+    assert!(matches!(parse_duration_bound("20x"), Err(Error::Invalid(_))));
+    assert!(matches!(parse_duration_bound("h"), Err(Error::Invalid(_))));
+    assert!(matches!(parse_duration_bound("18446744073709551615"), Err(Error::Invalid(_))));
+    assert!(matches!(parse_duration_bound("999999999999999999d"), Err(Error::Invalid(_))));
+    assert!(matches!(parse_duration_bound("18446744073709551615s1s"), Err(Error::Invalid(_))));
+
+    assert_eq!(parse_date_bound("2026-02-03T00:00:00Z").unwrap().to_rfc3339(), "2026-02-03T00:00:00+00:00");
+    assert!(parse_date_bound("2026-02-03").is_ok());
+    assert!(matches!(parse_date_bound("February"), Err(Error::Invalid(_))));
+    assert!(matches!("podcast".parse::<MediaType>(), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn filters_all_have_to_match()
+{
+    let since = parse_date_bound("2026-02-03T00:00:00Z").unwrap();
+
+    assert_eq!(matching(&EpisodeFilter::default()), vec![0, 1, 2, 3]);
+    // Episodes whose dates can't be read don't match date filters.
+    assert_eq!(matching(&EpisodeFilter { since: Some(since), ..Default::default() }), vec![0, 1]);
+    assert_eq!(matching(&EpisodeFilter { before: Some(since), ..Default::default() }), vec![3]);
+    assert_eq!(matching(&EpisodeFilter { min_duration: Some(Duration::from_secs(900)), ..Default::default() }), vec![0, 2]);
+    assert_eq!(matching(&EpisodeFilter { max_duration: Some(Duration::from_secs(900)), ..Default::default() }), vec![1, 2]);
+    assert_eq!(matching(&EpisodeFilter { title_regex: Some(parse_title_regex("^interview").unwrap()), ..Default::default() }), vec![0, 2]);
+    assert_eq!(matching(&EpisodeFilter { media_type: Some(MediaType::Video), ..Default::default() }), vec![1]);
+    assert_eq!(matching(&EpisodeFilter { has_enclosure: true, ..Default::default() }), vec![0, 1, 2]);
+
+    let filter = EpisodeFilter
+    {
+        since: Some(since),
+        title_regex: Some(parse_title_regex("interview").unwrap()),
+        media_type: Some(MediaType::Audio),
+        ..Default::default()
+    };
+    assert_eq!(matching(&filter), vec![0]);
+}
@@ -0,0 +1,89 @@
+use podclaw::Error;
+use podclaw::find::*;
+use podclaw::structs::Podcast;
+
+// Episodes are listed newest first, like in most feeds, so 'Budget Day' has index 0.
+const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+<channel>
+<title>Civics</title>
+<link>https://example.com/</link>
+<description>Politics explained.</description>
+<item>
+<title>Climate Policy Explained</title>
+<description>What the new climate policy means.</description>
+<pubDate>Wed, 04 Feb 2026 10:00:00 +0000</pubDate>
+<guid>climate-policy</guid>
+</item>
+<item>
+<title>Housing</title>
+<description>Rents, mortgages and the housing market.</description>
+<content:encoded><![CDATA[<p>Sponsored by <a href="https://climate.example.com">ads</a>. We ask about <b>climate</b> rules for builders.</p>]]></content:encoded>
+<pubDate>Tue, 03 Feb 2026 10:00:00 +0000</pubDate>
+<guid>housing</guid>
+</item>
+<item>
+<title>Budget Day</title>
+<description>Climate law and taxes in the budget.</description>
+<pubDate>Mon, 02 Feb 2026 10:00:00 +0000</pubDate>
+<guid>budget</guid>
+</item>
+</channel>
+</rss>"#;
+
+fn civics() -> Vec<Podcast>
+{
+    let mut podcast = Podcast::new();
+    podcast.alias = String::from("civics");
+    podcast.cache_content = String::from(FEED);
+    vec![podcast]
+}
+
+fn found_ids(query: &str) -> Vec<String>
+{
+    find_episodes(&parse_query(query).unwrap(), &civics(), &[0]).into_iter().map(|episode| episode.id).collect()
+}
+
+
+#[test]
+fn queries_parse_phrases_and_operators()
+{
+    let word = |word: &str| Query::Word(word.to_string());
+
+    assert_eq!(parse_query("Climate (policy OR law) -ads").unwrap(), Query::All(vec![
+        word("climate"),
+        Query::Any(vec![word("policy"), word("law")]),
+        Query::Not(Box::new(word("ads")))
+    ]));
+    assert_eq!(parse_query("\"new climate\" AND NOT taxes").unwrap(), Query::All(vec![
+        Query::Phrase(vec![String::from("new"), String::from("climate")]),
+        Query::Not(Box::new(word("taxes")))
+    ]));
+
+    for broken in ["", "climate OR", "(climate", "climate)"]
+    {
+        assert!(matches!(parse_query(broken), Err(Error::Invalid(_))), "{}", broken);
+    }
+}
+
+#[test]
+fn matches_are_ranked_by_title_and_phrase()
+{
+    // The title match ranks first, and the show notes count without their markup.
+    assert_eq!(found_ids("climate"), ["climate-policy", "housing", "budget"]);
+    assert_eq!(found_ids("climate -housing"), ["climate-policy", "budget"]);
+    assert_eq!(found_ids("\"climate law\" OR mortgages"), ["budget", "housing"]);
+    assert!(found_ids("example").is_empty());
+}
+
+#[test]
+fn results_carry_alias_index_and_date()
+{
+    let found = find_episodes(&parse_query("budget").unwrap(), &civics(), &[0]);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].alias, "civics");
+    assert_eq!(found[0].index, 0);
+    assert_eq!(found[0].title.as_deref(), Some("Budget Day"));
+    assert_eq!(found[0].published.as_deref(), Some("2026-02-02T10:00:00+00:00"));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use std::path::PathBuf;
+use podclaw::Error;
+use podclaw::http::{HttpClient, HttpRequest};
+use podclaw::manage_feeds::*;
+use podclaw::structs::{FeedMoveReason, Podcast};
+use podclaw::testing::{FakeClient, FakeResponse};
+use crate::common::sample_feed;
+
+const OLD_URL: &str = "https://old.example.com/feed.xml";
+const NEW_URL: &str = "https://new.example.com/feed.xml";
+
+fn added_podcast(client: &FakeClient, is_locked: bool) -> Vec<Podcast>
+{
+    let new = NewPodcast { alias: String::from("daily"), feed_url: OLD_URL.to_string(), download_path: PathBuf::from("Podcasts"), interval: 1, is_locked, ..Default::default() };
+    let mut podcasts = Vec::new();
+    add_podcast(client, new, &mut podcasts).unwrap();
+    podcasts
+}
+
+fn redirect(status: u16, location: &str) -> FakeResponse
+{
+    FakeResponse::status(status).with_header("Location", location)
+}
+
+
+#[test]
+fn permanent_redirects_move_the_feed()
+{
+    let client = FakeClient::new()
+        .with_response(OLD_URL, FakeResponse::ok(sample_feed("https://old.example.com")))
+        .with_response(NEW_URL, FakeResponse::ok(sample_feed("https://new.example.com")));
+    let mut podcasts = added_podcast(&client, false);
+
+    client.set_response(OLD_URL, redirect(301, NEW_URL));
+    let feed_move = update_podcast(&client, &mut podcasts[0]).unwrap().unwrap();
+
+    assert_eq!(feed_move, FeedMove { old_url: OLD_URL.to_string(), new_url: NEW_URL.to_string(), reason: FeedMoveReason::Redirect });
+    assert_eq!(podcasts[0].feed_url, NEW_URL);
+    assert_eq!(podcasts[0].previous_feed_urls[0].url, OLD_URL);
+}
+
+#[test]
+fn temporary_redirects_keep_the_feed()
+{
+    let client = FakeClient::new()
+        .with_response(OLD_URL, redirect(302, "/mirror.xml"))
+        .with_response("https://old.example.com/mirror.xml", redirect(301, NEW_URL))
+        .with_response(NEW_URL, FakeResponse::ok(sample_feed("https://new.example.com")));
+    let mut podcasts = added_podcast(&client, false);
+
+    assert_eq!(podcasts[0].feed_url, OLD_URL);
+    assert_eq!(update_podcast(&client, &mut podcasts[0]).unwrap(), None);
+    assert!(podcasts[0].previous_feed_urls.is_empty());
+}
+
+#[test]
+fn new_feed_url_tags_move_the_feed_once_the_new_one_works()
+{
+    let moved_feed = sample_feed("https://old.example.com")
+        .replace("<itunes:author>", &format!("<itunes:new-feed-url>{}</itunes:new-feed-url>\n<itunes:author>", NEW_URL));
+    let client = FakeClient::new()
+        .with_response(OLD_URL, FakeResponse::ok(sample_feed("https://old.example.com")));
+    let mut podcasts = added_podcast(&client, false);
+
+    client.set_response(OLD_URL, FakeResponse::ok(moved_feed));
+    assert_eq!(update_podcast(&client, &mut podcasts[0]).unwrap(), None);
+
+    client.set_response(NEW_URL, FakeResponse::ok(sample_feed("https://new.example.com")));
+    let feed_move = update_podcast(&client, &mut podcasts[0]).unwrap().unwrap();
+    assert_eq!(feed_move.reason, FeedMoveReason::NewFeedUrl);
+    assert_eq!(podcasts[0].feed_url, NEW_URL);
+    assert!(load_cached_feed(&podcasts[0]).unwrap().items[0].enclosure.as_ref().unwrap().url.starts_with("https://new.example.com"));
+}
+
+#[test]
+fn locked_podcasts_and_gone_feeds_stay_put()
+{
+    let client = FakeClient::new()
+        .with_response(OLD_URL, redirect(308, NEW_URL))
+        .with_response(NEW_URL, FakeResponse::ok(sample_feed("https://new.example.com")));
+    let mut podcasts = added_podcast(&client, true);
+    assert_eq!(podcasts[0].feed_url, OLD_URL);
+
+    podcasts[0].is_locked = false;
+    client.set_response(OLD_URL, FakeResponse::status(410));
+    match update_podcast(&client, &mut podcasts[0])
+    {
+        Err(Error::Network(message)) => assert!(message.contains("410") && message.contains("removed for good")),
+        other => panic!("Expected a network error, got {:?}", other.map(|_| ()))
+    }
+    assert_eq!(podcasts[0].feed_url, OLD_URL);
+}
+
+#[test]
+fn credentials_stay_on_the_original_host()
+{
+    let client = FakeClient::new()
+        .with_response(OLD_URL, redirect(302, NEW_URL))
+        .with_response(NEW_URL, FakeResponse::ok("feed"));
+
+    let mut request = HttpRequest::new(OLD_URL);
+    request.headers.push((String::from("Authorization"), String::from("Bearer t0ken")));
+    client.get(&request).unwrap();
+
+    let requests = client.requests();
+    assert!(requests[0].headers.iter().any(|(name, _)| name == "Authorization"));
+    assert!(requests[1].headers.is_empty());
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+use podclaw::Error;
+use podclaw::config::parse_config;
+use podclaw::http::{HttpClient, HttpRequest, HttpSettings, NetworkOverrides, ReqwestClient};
+use podclaw::testing::{FakeResponse, TestServer};
+
+fn quick_settings(retries: u32) -> HttpSettings
+{
+    HttpSettings::default().with_overrides(&[&NetworkOverrides { retries: Some(retries), retry_backoff: Some(1), ..Default::default() }])
+}
+
+
+#[test]
+fn server_errors_are_retried()
+{
+    let server = TestServer::start();
+    server.queue_route("/flaky", FakeResponse::status(503));
+    server.queue_route("/flaky", FakeResponse::ok("finally"));
+
+    let client = ReqwestClient::new(quick_settings(2)).unwrap();
+    let response = client.get(&HttpRequest::new(&server.url("/flaky"))).unwrap();
+
+    assert_eq!(response.body, b"finally");
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn retries_stop_at_the_configured_count()
+{
+    let server = TestServer::start();
+    server.set_route("/down", FakeResponse::status(500));
+
+    let client = ReqwestClient::new(quick_settings(1)).unwrap();
+    let response = client.get(&HttpRequest::new(&server.url("/down"))).unwrap();
+
+    assert_eq!(response.status, 500);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn client_errors_are_not_retried()
+{
+    let server = TestServer::start();
+
+    let client = ReqwestClient::new(quick_settings(3)).unwrap();
+    let response = client.get(&HttpRequest::new(&server.url("/missing"))).unwrap();
+
+    assert_eq!(response.status, 404);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn slow_downloads_outlast_the_read_timeout_but_stalled_ones_fail_once()
+{
+    let server = TestServer::start();
+    server.set_route("/slow", FakeResponse::ok("steady").with_pause(Duration::from_millis(200)));
+    server.set_route("/stalled", FakeResponse::ok("x").with_pause(Duration::from_millis(1500)));
+
+    let settings = HttpSettings { read_timeout: Duration::from_millis(500), ..quick_settings(2) };
+    let client = ReqwestClient::new(settings).unwrap();
+
+    assert_eq!(client.get(&HttpRequest::new(&server.url("/slow"))).unwrap().body, b"steady");
+    assert!(matches!(client.get(&HttpRequest::new(&server.url("/stalled"))), Err(Error::Network(_))));
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn user_agent_is_sent()
+{
+    let server = TestServer::start();
+    server.set_route("/", FakeResponse::ok(""));
+
+    let settings = HttpSettings::default().with_overrides(&[&NetworkOverrides { user_agent: Some(String::from("TestAgent/1.0")), ..Default::default() }]);
+    ReqwestClient::new(settings).unwrap().get(&HttpRequest::new(&server.url("/"))).unwrap();
+
+    let request = &server.requests()[0];
+    assert!(request.headers.iter().any(|(name, value)| name.eq_ignore_ascii_case("user-agent") && value == "TestAgent/1.0"));
+}
+
+#[test]
+fn invalid_proxies_are_rejected()
+{
+    let settings = HttpSettings::default().with_overrides(&[&NetworkOverrides { proxy: Some(String::from("not a url")), ..Default::default() }]);
+    assert!(matches!(ReqwestClient::new(settings), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn podcast_overrides_win_over_the_config()
+{
+    let config = parse_config("[network]\nconnect_timeout = 5\nretries = 4\nproxy = \"socks5://127.0.0.1:9050\"\n").unwrap();
+    let podcast_overrides = NetworkOverrides { retries: Some(0), proxy: Some(String::new()), ..Default::default() };
+
+    let settings = config.http_settings(&podcast_overrides);
+    assert_eq!(settings.connect_timeout, Duration::from_secs(5));
+    assert_eq!(settings.retries, 0);
+    assert_eq!(settings.proxy, None);
+    assert_eq!(settings.backoff_for(2), settings.retry_backoff * 4);
+}
+
+#[test]
+fn unknown_config_keys_are_errors()
+{
+    assert!(matches!(parse_config("[network]\ntimeout = 5\n"), Err(Error::Parse(_))));
+}
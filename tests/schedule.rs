@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use podclaw::Error;
+use podclaw::schedule::*;
+
+fn spec(every: &str) -> ScheduleSpec
+{
+    let every = podclaw::filters::parse_duration_bound(every).unwrap();
+    ScheduleSpec::new(every, PathBuf::from("/opt/pod claw/podclaw"), Some(PathBuf::from("/home/me/50%"))).unwrap()
+}
+
+
+#[test]
+fn units_run_update_all_with_the_same_config()
+{
+    let service = spec("6h").systemd_service();
+    assert!(service.contains("ExecStart=\"/opt/pod claw/podclaw\" update --all\n"));
+    assert!(service.contains("Environment=\"PODCLAW_CONFIG_DIR=/home/me/50%%\"\n"));
+
+    let timer = spec("6h").systemd_timer();
+    assert!(timer.contains("OnUnitActiveSec=21600s\n") && timer.contains("RandomizedDelaySec=600s\n"));
+    assert!(matches!(ScheduleSpec::new(Duration::from_secs(30), PathBuf::from("podclaw"), None), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn cron_lines_need_an_even_cadence()
+{
+    assert_eq!(spec("15m").cron_line().unwrap(),
+        "*/15 * * * * PODCLAW_CONFIG_DIR='/home/me/50\\%' '/opt/pod claw/podclaw' update --all # podclaw schedule");
+    assert!(spec("1h").cron_line().unwrap().starts_with("0 * * * * "));
+    assert!(spec("8h").cron_line().unwrap().starts_with("0 */8 * * * "));
+    assert!(spec("1d").cron_line().unwrap().starts_with("0 0 * * * "));
+    assert!(matches!(spec("7m").cron_line(), Err(Error::Invalid(_))));
+    assert!(matches!(spec("5h").cron_line(), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn only_podclaws_cron_line_is_touched()
+{
+    let crontab = "MAILTO=me\n0 5 * * * backup\n";
+    let installed = with_cron_line(crontab, "0 * * * * podclaw update --all # podclaw schedule");
+    let replaced = with_cron_line(&installed, "0 0 * * * podclaw update --all # podclaw schedule");
+
+    assert_eq!(replaced, "MAILTO=me\n0 5 * * * backup\n0 0 * * * podclaw update --all # podclaw schedule\n");
+    assert_eq!(find_cron_line(&replaced), Some("0 0 * * * podclaw update --all # podclaw schedule"));
+    assert_eq!(without_cron_line(&replaced), crontab);
+    assert_eq!(without_cron_line(&with_cron_line("", "line # podclaw schedule")), "");
+}
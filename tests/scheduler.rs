@@ -0,0 +1,63 @@
+use std::time::{Duration, SystemTime};
+use podclaw::scheduler::*;
+use podclaw::structs::Podcast;
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+fn podcast(alias: &str, interval: Duration, cache_age: Duration, now: SystemTime) -> Podcast
+{
+    let mut podcast = Podcast::new();
+    podcast.alias = alias.to_string();
+    podcast.update_interval = interval;
+    podcast.cache_time = now - cache_age;
+    podcast
+}
+
+
+#[test]
+fn podcasts_are_due_after_their_interval_with_jitter()
+{
+    let now = SystemTime::now();
+    let mut locked = podcast("locked", HOUR, 2 * HOUR, now);
+    locked.is_locked = true;
+    let mut podcasts = vec![podcast("stale", HOUR, 2 * HOUR, now), podcast("fresh", 20 * HOUR, HOUR, now), locked];
+
+    let mut scheduler = Scheduler::default();
+    scheduler.sync(&podcasts, now);
+    assert_eq!(scheduler.due(&podcasts, now + Duration::from_secs(180)), vec![0]);
+    assert_eq!(scheduler.due_at("locked"), None);
+
+    // The fresh podcast is due after the rest of its interval, plus at most a twentieth of it.
+    let fresh_due = scheduler.due_at("fresh").unwrap();
+    assert!(fresh_due >= now + 19 * HOUR && fresh_due <= now + 20 * HOUR);
+
+    // Once refreshed, the stale podcast waits for its full interval again.
+    podcasts[0].cache_time = now;
+    scheduler.sync(&podcasts, now);
+    assert!(scheduler.due(&podcasts, now + Duration::from_secs(180)).is_empty());
+    assert!(scheduler.due_at("stale").unwrap() >= now + HOUR);
+
+    podcasts.remove(0);
+    scheduler.sync(&podcasts, now);
+    assert_eq!(scheduler.due_at("stale"), None);
+    assert_eq!(scheduler.next_due(), Some(fresh_due));
+}
+
+#[test]
+fn refreshes_are_spaced_out_and_failures_retried_later()
+{
+    let now = SystemTime::now();
+    let mut podcasts = vec![podcast("constant", Duration::ZERO, Duration::ZERO, now)];
+
+    let mut scheduler = Scheduler::default();
+    scheduler.sync(&podcasts, now);
+    assert_eq!(scheduler.due(&podcasts, now), vec![0]);
+
+    podcasts[0].cache_time = now + Duration::from_secs(1);
+    scheduler.sync(&podcasts, now);
+    assert_eq!(scheduler.due_at("constant"), Some(podcasts[0].cache_time + MIN_REFRESH_GAP));
+
+    scheduler.postpone(&podcasts[0], now);
+    let retry = scheduler.due_at("constant").unwrap();
+    assert!(retry >= now + MIN_REFRESH_GAP && retry <= now + MIN_REFRESH_GAP * 2);
+}
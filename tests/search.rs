@@ -0,0 +1,67 @@
+mod common;
+
+use podclaw::search::*;
+use podclaw::testing::{FakeClient, FakeResponse, TestServer};
+use crate::common::{podclaw, sample_feed, stdout_of};
+
+const ITUNES_ANSWER: &str = r#"{"resultCount": 3, "results": [
+    {"collectionName": "The Daily Test", "artistName": "Tester", "feedUrl": "https://example.com/feed.xml"},
+    {"collectionName": "No Feed Here", "artistName": "Nobody"},
+    {"collectionName": "Test Weekly", "feedUrl": "https://example.com/weekly.xml"}
+]}"#;
+
+
+#[test]
+fn itunes_results_skip_shows_without_feeds()
+{
+    let settings = SearchSettings { base_url: Some(String::from("https://search.example.com/")), limit: Some(5), ..Default::default() };
+    let client = FakeClient::new().with_response(
+        "https://search.example.com/search?media=podcast&entity=podcast&term=daily+test&limit=5", FakeResponse::ok(ITUNES_ANSWER));
+
+    let results = search_podcasts(&client, &settings, None, "daily test").unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0], SearchResult { title: String::from("The Daily Test"), author: Some(String::from("Tester")), feed_url: String::from("https://example.com/feed.xml") });
+    assert_eq!(results[1].author, None);
+}
+
+#[test]
+fn podcast_index_requests_are_signed()
+{
+    let settings = SearchSettings
+    {
+        provider: SearchProvider::PodcastIndex, base_url: Some(String::from("https://index.example.com/api/1.0")),
+        api_key: Some(String::from("KEY")), ..Default::default()
+    };
+    let client = FakeClient::new().with_response(
+        "https://index.example.com/api/1.0/search/byterm?q=daily&max=10",
+        FakeResponse::ok(r#"{"status": "true", "feeds": [{"title": "The Daily Test", "author": "Tester", "url": "https://example.com/feed.xml"}]}"#));
+
+    let results = search_podcasts(&client, &settings, Some("SECRET"), "daily").unwrap();
+    assert_eq!(results[0].feed_url, "https://example.com/feed.xml");
+
+    let request = &client.requests()[0];
+    let header = |name: &str| request.headers.iter().find(|(header_name, _)| header_name == name).map(|(_, value)| value.clone());
+    assert_eq!(header("X-Auth-Key").as_deref(), Some("KEY"));
+    assert_eq!(header("Authorization").map(|signature| signature.len()), Some(40));
+    assert!(header("X-Auth-Date").is_some());
+}
+
+#[test]
+fn search_results_can_be_added_directly()
+{
+    let server = TestServer::start();
+    server.set_route("/feed.xml", FakeResponse::ok(sample_feed(&server.url(""))));
+    let answer = ITUNES_ANSWER.replace("https://example.com", &server.url(""));
+    server.set_route("/search?media=podcast&entity=podcast&term=daily&limit=10", FakeResponse::ok(answer));
+
+    let config = tempfile::tempdir().unwrap();
+    std::fs::write(config.path().join("config.toml"), format!("[search]\nbase_url = \"{}\"\n", server.url(""))).unwrap();
+
+    let searched = stdout_of(&podclaw(config.path(), &["search", "daily"]));
+    assert!(searched.contains("The Daily Test") && searched.contains(&server.url("/weekly.xml")));
+
+    // Several results and no terminal to ask with, so '--pick' is needed.
+    assert_eq!(podclaw(config.path(), &["search", "daily", "--add", "daily", "-p", "Podcasts", "-i", "24"]).status.code(), Some(1));
+    assert!(podclaw(config.path(), &["search", "daily", "--add", "daily", "--pick", "0", "-p", "Podcasts", "-i", "24"]).status.success());
+    assert!(stdout_of(&podclaw(config.path(), &["list"])).contains("daily"));
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use podclaw::Error;
+use podclaw::secrets::*;
+use podclaw::testing::{FakeResponse, TestServer};
+use crate::common::{podclaw, podclaw_with_env, sample_feed};
+
+const PASSPHRASE: (&str, &str) = ("PODCLAW_PASSPHRASE", "correct horse");
+
+
+#[test]
+fn secrets_round_trip_and_need_the_passphrase()
+{
+    let folder = tempfile::tempdir().unwrap();
+    let path = folder.path().join("podclaw_secrets.bin");
+
+    let mut secrets = SecretStore::new();
+    secrets.set("patreon", "hunter2").unwrap();
+    save_secrets_at(&secrets, &path, "correct horse").unwrap();
+
+    let contents = std::fs::read(&path).unwrap();
+    assert!(!contents.windows(7).any(|window| window == b"hunter2"));
+
+    let reopened = open_secrets_at(&path, "correct horse").unwrap();
+    assert_eq!(reopened.get("patreon"), Some("hunter2"));
+    assert!(matches!(open_secrets_at(&path, "wrong"), Err(Error::Invalid(_))));
+    assert!(matches!(secrets.set("has space", "x"), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn podcasts_use_stored_secrets_by_name()
+{
+    let server = TestServer::start();
+    server.set_route("/feed.xml", FakeResponse::ok(sample_feed(&server.url(""))));
+    let config = tempfile::tempdir().unwrap();
+
+    let stored = podclaw_with_env(config.path(), &["secret", "set", "patreon", "--value", "hunter2"], &[PASSPHRASE]);
+    assert!(stored.status.success());
+
+    let added = podclaw_with_env(config.path(), &["add", "private", &server.url("/feed.xml"), "Podcasts", "24",
+        "--username", "listener", "--password-secret", "patreon"], &[PASSPHRASE]);
+    assert!(added.status.success());
+    assert_eq!(server.requests()[0].headers.iter().find(|(name, _)| name == "authorization").unwrap().1, "Basic bGlzdGVuZXI6aHVudGVyMg==");
+
+    let storage = std::fs::read(config.path().join("podclaw_storage.bin")).unwrap();
+    assert!(!storage.windows(7).any(|window| window == b"hunter2"));
+
+    // Without a passphrase or a terminal, the secrets file stays locked.
+    assert_eq!(podclaw(config.path(), &["update", "private"]).status.code(), Some(1));
+    assert!(podclaw_with_env(config.path(), &["update", "private"], &[PASSPHRASE]).status.success());
+}
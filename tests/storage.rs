@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use podclaw::structs::Podcast;
+use podclaw::utils::{get_storage_at, save_storage_at};
+
+// The layout Podclaw 1.0.1 wrote to its storage file.
+#[derive(Serialize)]
+struct OldPodcast
+{
+    alias: String,
+    feed_url: String,
+    download_path: PathBuf,
+    update_interval: Duration,
+    cache_time: SystemTime,
+    cache_content: String,
+    is_locked: bool
+}
+
+
+#[test]
+fn missing_storage_is_created()
+{
+    let folder = tempfile::tempdir().unwrap();
+    let path = folder.path().join("podclaw_storage.bin");
+
+    assert!(get_storage_at(&path).unwrap().is_empty());
+    assert!(path.exists());
+}
+
+#[test]
+fn storage_round_trips()
+{
+    let folder = tempfile::tempdir().unwrap();
+    let path = folder.path().join("podclaw_storage.bin");
+
+    let mut podcast = Podcast::new();
+    podcast.alias = String::from("daily");
+    podcast.network.retries = Some(5);
+    save_storage_at(&[podcast], &path).unwrap();
+
+    let podcasts = get_storage_at(&path).unwrap();
+    assert_eq!(podcasts[0].alias, "daily");
+    assert_eq!(podcasts[0].network.retries, Some(5));
+}
+
+#[test]
+fn old_storage_is_migrated()
+{
+    let folder = tempfile::tempdir().unwrap();
+    let path = folder.path().join("podclaw_storage.bin");
+
+    let old_podcasts = vec![OldPodcast
+    {
+        alias: String::from("old"),
+        feed_url: String::from("https://example.com/feed.xml"),
+        download_path: PathBuf::from("Podcasts"),
+        update_interval: Duration::from_secs(3600),
+        cache_time: UNIX_EPOCH,
+        cache_content: String::new(),
+        is_locked: true
+    }];
+    std::fs::write(&path, bincode::serialize(&old_podcasts).unwrap()).unwrap();
+
+    let podcasts = get_storage_at(&path).unwrap();
+    assert_eq!(podcasts[0].alias, "old");
+    assert!(podcasts[0].is_locked);
+    assert!(podcasts[0].network.is_empty());
+
+    // Saving moves the file to the current format, with every field podcasts have now.
+    let mut podcasts = podcasts;
+    podcasts[0].auto_download.enabled = true;
+    save_storage_at(&podcasts, &path).unwrap();
+    assert_eq!(&std::fs::read(&path).unwrap()[..4], b"PCLW");
+    assert!(get_storage_at(&path).unwrap()[0].auto_download.enabled);
+}
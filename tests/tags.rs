@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+use podclaw::Error;
+use podclaw::config::parse_config;
+use podclaw::manage_feeds::*;
+use podclaw::structs::Podcast;
+use podclaw::utils::{all_tags, podcasts_tagged};
+
+fn tagged_podcast(alias: &str, tags: &[&str]) -> Podcast
+{
+    let mut podcast = Podcast::new();
+    podcast.alias = alias.to_string();
+    podcast.tags = tags.iter().map(|tag| tag.to_string()).collect();
+    podcast
+}
+
+fn strings(raw: &[&str]) -> Vec<String>
+{
+    raw.iter().map(|value| value.to_string()).collect()
+}
+
+
+#[test]
+fn tags_are_normalized_and_deduplicated()
+{
+    let mut podcast = tagged_podcast("daily", &["news"]);
+
+    assert_eq!(tag_podcast(&mut podcast, &strings(&["News", " Tech ", "tech"])).unwrap(), strings(&["tech"]));
+    assert_eq!(podcast.tags, strings(&["news", "tech"]));
+    assert!(matches!(tag_podcast(&mut podcast, &strings(&["two words"])), Err(Error::Invalid(_))));
+
+    assert_eq!(untag_podcast(&mut podcast, &strings(&["NEWS", "sports"])).unwrap(), strings(&["news"]));
+    assert_eq!(podcast.tags, strings(&["tech"]));
+
+    podcast.is_locked = true;
+    assert!(matches!(tag_podcast(&mut podcast, &strings(&["news"])), Err(Error::Locked(_))));
+}
+
+#[test]
+fn podcasts_are_found_by_tag()
+{
+    let podcasts = vec![tagged_podcast("daily", &["news"]), tagged_podcast("bytes", &["tech"]), tagged_podcast("both", &["tech", "news"])];
+
+    assert_eq!(podcasts_tagged(&strings(&["news"]), &podcasts).unwrap(), vec![0, 2]);
+    assert_eq!(podcasts_tagged(&strings(&["Tech", "news"]), &podcasts).unwrap(), vec![0, 1, 2]);
+    assert!(matches!(podcasts_tagged(&strings(&["sports"]), &podcasts), Err(Error::NotFound(_))));
+
+    assert_eq!(all_tags(&podcasts), vec![(String::from("news"), 2), (String::from("tech"), 2)]);
+}
+
+#[test]
+fn tag_defaults_come_from_the_first_tag_that_sets_them()
+{
+    let config = parse_config("[tags.news]\ndownload_path = \"/podcasts/news\"\n\n[tags.daily]\ninterval = 6\ndownload_path = \"/podcasts/daily\"\n").unwrap();
+
+    let defaults = config.tag_defaults(&strings(&["sports", "News", "daily"]));
+    assert_eq!(defaults.download_path, Some(PathBuf::from("/podcasts/news")));
+    assert_eq!(defaults.interval, Some(6));
+
+    assert_eq!(config.tag_defaults(&[]).interval, None);
+    assert!(parse_config("[tags.news]\npath = \"/podcasts\"\n").is_err());
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use podclaw::Error;
+use podclaw::config::parse_config;
+use podclaw::theme::*;
+
+fn environment(variables: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String>
+{
+    move |name| variables.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+}
+
+#[test]
+fn colors_follow_the_palette()
+{
+    let themes = BTreeMap::new();
+
+    assert_eq!(build_text_deco("default", &themes, Some(Palette::TrueColor)).unwrap().completion, "\x1b[1m\x1b[38;2;51;153;51m[✓]\x1b[0m");
+    assert_eq!(build_text_deco("default", &themes, Some(Palette::Ansi256)).unwrap().important, "\x1b[1m\x1b[38;5;208m[#]\x1b[0m");
+    assert_eq!(build_text_deco("default", &themes, Some(Palette::Ansi16)).unwrap().error, "\x1b[1m\x1b[91m[!]\x1b[0m");
+    assert_eq!(build_text_deco("default", &themes, None).unwrap().general, "[*]");
+    assert_eq!(build_text_deco("plain", &themes, Some(Palette::TrueColor)).unwrap().general, "[*]");
+}
+
+#[test]
+fn user_themes_override_markers_of_their_base()
+{
+    let config = parse_config(r##"
+[themes.mine]
+base = "basic"
+completion = "#00ff00"
+error = { symbol = "ERR" }
+
+[themes.calm]
+base = "mine"
+error = "blue"
+"##).unwrap();
+
+    let mine = build_text_deco("mine", &config.themes, Some(Palette::TrueColor)).unwrap();
+    assert_eq!(mine.completion, "\x1b[1m\x1b[38;2;0;255;0m[✓]\x1b[0m");
+    assert_eq!(mine.error, "\x1b[1m\x1b[91mERR\x1b[0m");
+    assert_eq!(mine.general, "\x1b[1m\x1b[96m[*]\x1b[0m");
+
+    assert_eq!(build_text_deco("calm", &config.themes, Some(Palette::Ansi16)).unwrap().error, "\x1b[1m\x1b[34mERR\x1b[0m");
+    assert!(matches!(build_text_deco("neon", &config.themes, None), Err(Error::Invalid(_))));
+
+    let looping = parse_config("[themes.a]\nbase = \"b\"\n\n[themes.b]\nbase = \"a\"\n").unwrap();
+    assert!(matches!(build_text_deco("a", &looping.themes, None), Err(Error::Invalid(_))));
+
+    let bad_color = parse_config("[themes.bad]\ngeneral = \"#12345\"\n").unwrap();
+    assert!(matches!(build_text_deco("bad", &bad_color.themes, None), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn colors_depend_on_the_terminal_and_no_color()
+{
+    assert!(use_colors(ColorMode::Auto, true, environment(&[])));
+    assert!(!use_colors(ColorMode::Auto, false, environment(&[])));
+    assert!(!use_colors(ColorMode::Auto, true, environment(&[("NO_COLOR", "1")])));
+    assert!(use_colors(ColorMode::Auto, true, environment(&[("NO_COLOR", "")])));
+    assert!(!use_colors(ColorMode::Auto, true, environment(&[("TERM", "dumb")])));
+    assert!(use_colors(ColorMode::Always, false, environment(&[("NO_COLOR", "1")])));
+    assert!(!use_colors(ColorMode::Never, true, environment(&[])));
+
+    assert_eq!(detect_palette(environment(&[("COLORTERM", "truecolor")])), Palette::TrueColor);
+    assert_eq!(detect_palette(environment(&[("TERM", "xterm-256color")])), Palette::Ansi256);
+    assert_eq!(detect_palette(environment(&[("TERM", "linux")])), Palette::Ansi16);
+}